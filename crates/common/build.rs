@@ -1,15 +1,33 @@
 //! Pathfinder build script.
 //!
-//! Just sets up `vergen` to query our git information for the build.
+//! Sets up `vergen` to query our git information for the build, and generates the
+//! function selectors known to the `selector!` macro.
+
+use sha3::{Digest, Keccak256};
+use std::fmt::Write;
+use std::{env, fs, path::Path};
+
+/// Function names whose selectors can be created with `selector!`.
+const SELECTOR_NAMES: &[&str] = &["constructor", "handle_deposit", "handle_token_deposit"];
 
 pub fn main() {
+    generate_selectors();
+
     let force_version_env_var_name = "PATHFINDER_FORCE_VERSION";
+    let force_git_sha_env_var_name = "PATHFINDER_FORCE_GIT_SHA";
 
     println!("cargo:rerun-if-env-changed={force_version_env_var_name}");
+    println!("cargo:rerun-if-env-changed={force_git_sha_env_var_name}");
 
     if let Ok(version) = std::env::var(force_version_env_var_name) {
         if !version.is_empty() {
             println!("cargo:rustc-env=VERGEN_GIT_DESCRIBE={version}");
+            // Builds without a .git directory, such as the docker image, can also set the hash.
+            let git_sha = std::env::var(force_git_sha_env_var_name)
+                .ok()
+                .filter(|sha| !sha.is_empty())
+                .unwrap_or_else(|| "unknown".to_owned());
+            println!("cargo:rustc-env=VERGEN_GIT_SHA={git_sha}");
             return;
         }
     }
@@ -17,9 +35,27 @@ pub fn main() {
     // at 7.0.0 default enables everything compiled in, selected with feature-flags
     const ENABLE_DIRTY: bool = true;
     const ENABLE_TAGS: bool = true;
+    const SHORT_SHA: bool = true;
     vergen::EmitBuilder::builder()
         .fail_on_error()
         .git_describe(ENABLE_DIRTY, ENABLE_TAGS, None)
+        .git_sha(SHORT_SHA)
         .emit()
         .expect("vergen failed; this is probably due to missing .git directory");
 }
+
+/// Generates selectors.rs, which maps each of [SELECTOR_NAMES] to its Starknet Keccak hash
+/// as big-endian bytes.
+fn generate_selectors() {
+    let mut code = String::from("const SELECTORS: &[(&str, [u8; 32])] = &[\n");
+    for name in SELECTOR_NAMES {
+        let mut digest: [u8; 32] = Keccak256::digest(name.as_bytes()).into();
+        // Truncated to 250 bits.
+        digest[0] &= 0x03;
+        writeln!(code, "    ({name:?}, {digest:?}),").unwrap();
+    }
+    code.push_str("];\n");
+
+    let dest_path = Path::new(&env::var_os("OUT_DIR").unwrap()).join("selectors.rs");
+    fs::write(dest_path, code).expect("could not write $OUT_DIR/selectors.rs");
+}
@@ -5,6 +5,15 @@ use crate::{felt, BlockHash};
 /// Vergen string
 pub const VERGEN_GIT_DESCRIBE: &str = env!("VERGEN_GIT_DESCRIBE");
 
+/// Short hash of the git commit, or `unknown` for builds with a forced version but no hash.
+pub const VERGEN_GIT_SHA: &str = env!("VERGEN_GIT_SHA");
+
+/// Latest Starknet version supported by this release.
+///
+/// This is the version of the `cairo-lang` package pinned in `py/requirements-dev.txt`, which
+/// executes calls and fee estimates, so it has to be bumped together with that pin.
+pub const STARKNET_VERSION: &str = "0.11.1";
+
 /// User agent used in http clients
 pub const USER_AGENT: &str = concat!("starknet-pathfinder/", env!("VERGEN_GIT_DESCRIBE"));
 
@@ -23,3 +32,24 @@ pub const INTEGRATION_GENESIS_HASH: BlockHash = BlockHash(felt!(
 pub const TESTNET2_GENESIS_HASH: BlockHash = BlockHash(felt!(
     "04163f64ea0258f21fd05b478e2306ab2daeb541bdbd3bf29a9874dc5cd4b64e"
 ));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starknet_version_matches_cairo_lang() {
+        let requirements = include_str!("../../../py/requirements-dev.txt");
+        let cairo_lang = requirements
+            .lines()
+            .find_map(|line| line.strip_prefix("cairo-lang=="))
+            .expect("cairo-lang is pinned");
+
+        // Post-releases such as 0.11.1.1 support the same Starknet version.
+        assert!(
+            cairo_lang == STARKNET_VERSION
+                || cairo_lang.starts_with(&format!("{STARKNET_VERSION}.")),
+            "cairo-lang {cairo_lang} does not match Starknet {STARKNET_VERSION}"
+        );
+    }
+}
@@ -9,6 +9,7 @@ use stark_hash::Felt;
 
 pub mod consts;
 mod macros;
+pub mod selector_registry;
 #[cfg(feature = "test-utils")]
 pub mod test_utils;
 
@@ -18,6 +19,7 @@ pub struct ContractAddress(Felt);
 
 macros::starkhash251::newtype!(ContractAddress);
 macros::starkhash251::deserialization!(ContractAddress);
+macros::starkhash251::from_str!(ContractAddress, ParseContractAddressError);
 
 /// A nonce that is associated with a particular deployed Starknet contract
 /// distinguishing it from other contracts that use the same contract class.
@@ -61,6 +63,10 @@ pub struct ClassCommitmentLeafHash(pub Felt);
 
 /// A Starknet contract's state hash. This is the value stored
 /// in the global state tree.
+///
+/// The hash is a Pedersen hash chain over the contract's class hash, storage root and nonce,
+/// and can therefore not be decoded back into these components. Instead, the components are
+/// stored alongside the hash in the `contract_states` table.
 #[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ContractStateHash(pub Felt);
 
@@ -83,7 +89,7 @@ pub struct ContractClass {
 }
 
 /// Entry point of a Starknet `call`.
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EntryPoint(pub Felt);
 
 impl EntryPoint {
@@ -123,11 +129,24 @@ pub struct StorageAddress(Felt);
 
 macros::starkhash251::newtype!(StorageAddress);
 macros::starkhash251::deserialization!(StorageAddress);
+macros::starkhash251::from_str!(StorageAddress, ParseStorageAddressError);
 
 /// The value of a storage element for a Starknet contract.
 #[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize, PartialOrd, Ord)]
 pub struct StorageValue(pub Felt);
 
+impl StorageValue {
+    /// The largest storage value, i.e. the field modulus minus one.
+    pub const MAX: Self = Self(felt!(
+        "0x800000000000011000000000000000000000000000000000000000000000000"
+    ));
+
+    /// Returns true if the storage value is zero, which is also the value of an unset storage slot.
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
 /// The commitment for the state of a Starknet block.
 ///
 /// Before Starknet v0.11.0 this was equivalent to [StorageCommitment].
@@ -167,12 +186,19 @@ impl StateCommitment {
 #[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StorageCommitment(pub Felt);
 
+impl StorageCommitment {
+    /// Returns true if this is the root of an empty storage commitment tree.
+    pub fn is_empty_tree(&self) -> bool {
+        *self == Self::ZERO
+    }
+}
+
 /// A Starknet block hash.
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct BlockHash(pub Felt);
 
 /// A Starknet block number.
-#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd)]
+#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockNumber(u64);
 
 macros::i64_backed_u64::to_from_sql!(BlockNumber);
@@ -202,7 +228,7 @@ pub struct EventCommitment(pub Felt);
 pub struct TransactionCommitment(pub Felt);
 
 /// A Starknet transaction hash.
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct TransactionHash(pub Felt);
 
 /// A Starknet transaction index.
@@ -582,6 +608,20 @@ pub fn truncated_keccak(mut plain: [u8; 32]) -> Felt {
     Felt::from_be_bytes(plain).expect("cannot overflow: smaller than modulus")
 }
 
+/// Creates a [Felt] from up to 251 bits in MSB order, the inverse of [bits_from_felt].
+pub fn felt_from_bits(
+    bits: &bitvec::slice::BitSlice<bitvec::order::Msb0, u8>,
+) -> Result<Felt, stark_hash::OverflowError> {
+    Felt::from_bits(bits)
+}
+
+/// The 251 least significant bits of `felt` in MSB order, i.e. the path of a Merkle tree key.
+pub fn bits_from_felt(
+    felt: Felt,
+) -> impl std::ops::Deref<Target = bitvec::slice::BitSlice<bitvec::order::Msb0, u8>> {
+    felt.view_bits().to_bitvec()
+}
+
 /// Calculate class commitment tree leaf hash value.
 ///
 /// See: <https://docs.starknet.io/documentation/starknet_versions/upcoming_versions/#state_commitment>
@@ -600,6 +640,23 @@ pub fn calculate_class_commitment_leaf_hash(
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn bits_round_trip() {
+        use super::{bits_from_felt, felt_from_bits, StorageAddress};
+
+        let felt =
+            crate::felt!("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff01");
+        let bits = bits_from_felt(felt);
+        assert_eq!(bits.len(), 251);
+        assert_eq!(felt_from_bits(&bits).unwrap(), felt);
+
+        let address = StorageAddress::from_bits(&bits).unwrap();
+        assert_eq!(address.view_bits(), &*bits);
+
+        let too_long = bitvec::vec::BitVec::<bitvec::order::Msb0, u8>::repeat(true, 252);
+        felt_from_bits(&too_long).unwrap_err();
+    }
+
     mod starknet_version {
         use super::super::StarknetVersion;
 
@@ -620,6 +677,108 @@ mod tests {
         }
     }
 
+    mod storage_value {
+        use super::super::StorageValue;
+        use stark_hash::Felt;
+
+        #[test]
+        fn zero() {
+            assert!(StorageValue::ZERO.is_zero());
+            assert!(!StorageValue(Felt::from_u64(1)).is_zero());
+            assert!(!StorageValue::MAX.is_zero());
+        }
+
+        #[test]
+        fn max_is_the_largest_felt() {
+            assert_eq!(StorageValue::MAX.0 + Felt::from_u64(1), Felt::ZERO);
+            assert!(Felt::from_hex_str(
+                "0x800000000000011000000000000000000000000000000000000000000000001"
+            )
+            .is_err());
+        }
+    }
+
+    mod state_commitment {
+        use super::super::{ClassCommitment, StateCommitment, StorageCommitment};
+        use crate::felt;
+
+        #[test]
+        fn without_class_commitment_is_storage_commitment() {
+            // Prior to Starknet v0.11 the state commitment was just the storage commitment.
+            let storage_commitment = StorageCommitment(felt!("0x1234"));
+            let state_commitment =
+                StateCommitment::calculate(storage_commitment, ClassCommitment::ZERO);
+
+            assert_eq!(state_commitment.0, storage_commitment.0);
+        }
+
+        #[test]
+        fn with_class_commitment_is_hashed() {
+            let storage_commitment = StorageCommitment(felt!("0x1234"));
+            let class_commitment = ClassCommitment(felt!("0x5678"));
+            let state_commitment = StateCommitment::calculate(storage_commitment, class_commitment);
+
+            // No v0.11 block fixture carries its storage and class commitments separately, so
+            // this is `poseidon_hash_many(['STARKNET_STATE_V0', 0x1234, 0x5678])` as specified
+            // by cairo-lang's state.cairo (linked from `calculate`), evaluated with a Python
+            // port of the reference implementation in `crates/stark_poseidon/poseidon.ipynb`
+            // using the round constants in `crates/stark_poseidon/spec/poseidon3.txt`. The same
+            // port reproduces the cairo-lang vectors in stark_poseidon's tests.
+            let expected = StateCommitment(felt!(
+                "0x363f39e8de3c59dcc270d52eedb08bad69d096491da8403d5526464ccc8748c"
+            ));
+            assert_eq!(state_commitment, expected);
+        }
+
+        #[test]
+        fn empty_storage_commitment_tree() {
+            assert!(StorageCommitment::ZERO.is_empty_tree());
+            assert!(!StorageCommitment(felt!("0x1234")).is_empty_tree());
+        }
+    }
+
+    mod contract_address_from_str {
+        use crate::{felt, ContractAddress};
+
+        #[test]
+        fn valid() {
+            let expected = ContractAddress::new_or_panic(felt!("0x1234"));
+            assert_eq!("0x1234".parse::<ContractAddress>().unwrap(), expected);
+            assert_eq!(ContractAddress::from_hex_str("1234").unwrap(), expected);
+        }
+
+        #[test]
+        fn invalid_hex() {
+            let error = ContractAddress::from_hex_str("0xabcz").unwrap_err();
+            assert_eq!(error.input, "0xabcz");
+        }
+
+        #[test]
+        fn too_many_bits() {
+            let input = "0x0800000000000000000000000000000000000000000000000000000000000000";
+            let error = input.parse::<ContractAddress>().unwrap_err();
+            assert_eq!(error.input, input);
+        }
+    }
+
+    mod storage_address_from_str {
+        use crate::{felt, StorageAddress};
+
+        #[test]
+        fn valid() {
+            let expected = StorageAddress::new_or_panic(felt!("0x1234"));
+            assert_eq!("0x1234".parse::<StorageAddress>().unwrap(), expected);
+            assert_eq!(StorageAddress::from_hex_str("1234").unwrap(), expected);
+        }
+
+        #[test]
+        fn too_many_bits() {
+            let input = "0x0800000000000000000000000000000000000000000000000000000000000000";
+            let error = input.parse::<StorageAddress>().unwrap_err();
+            assert_eq!(error.input, input);
+        }
+    }
+
     mod block_id_serde {
         use super::super::BlockId;
 
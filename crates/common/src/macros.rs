@@ -226,6 +226,14 @@ pub(super) mod starkhash251 {
                 pub fn view_bits(&self) -> &bitvec::slice::BitSlice<bitvec::order::Msb0, u8> {
                     self.0.view_bits()
                 }
+
+                /// Creates the key from up to 251 bits, the inverse of [view_bits](Self::view_bits).
+                pub fn from_bits(
+                    bits: &bitvec::slice::BitSlice<bitvec::order::Msb0, u8>,
+                ) -> Result<Self, stark_hash::OverflowError> {
+                    // Up to 251 bits never exceed a key's maximum.
+                    $crate::felt_from_bits(bits).map(Self)
+                }
             }
         };
     }
@@ -282,7 +290,55 @@ pub(super) mod starkhash251 {
             }
         };
     }
-    pub(crate) use {deserialization, newtype};
+    /// Adds a `from_hex_str` constructor and a [FromStr](std::str::FromStr) implementation which
+    /// reject values with more than 251 bits set. Also defines the error type `$error` returned by both.
+    macro_rules! from_str {
+        ($target:ty, $error:ident) => {
+            #[doc = concat!("Error returned when parsing a [", stringify!($target), "] fails.")]
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct $error {
+                pub input: String,
+                pub reason: String,
+            }
+
+            impl std::fmt::Display for $error {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(
+                        f,
+                        "Invalid {} {:?}: {}",
+                        stringify!($target),
+                        self.input,
+                        self.reason
+                    )
+                }
+            }
+
+            impl std::error::Error for $error {}
+
+            impl $target {
+                /// Parses a hex string with an optional "0x" prefix.
+                pub fn from_hex_str(input: &str) -> Result<Self, $error> {
+                    let error = |reason: String| $error {
+                        input: input.to_owned(),
+                        reason,
+                    };
+
+                    let hash = Felt::from_hex_str(input).map_err(|e| error(e.to_string()))?;
+                    Self::new(hash).ok_or_else(|| error("At most 251-bit value".to_owned()))
+                }
+            }
+
+            impl std::str::FromStr for $target {
+                type Err = $error;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    Self::from_hex_str(s)
+                }
+            }
+        };
+    }
+
+    pub(crate) use {deserialization, from_str, newtype};
 }
 
 pub(super) mod fmt {
@@ -0,0 +1,88 @@
+//! Compile-time lookup of Starknet function selectors.
+//!
+//! The selectors of the function names listed in `build.rs` are hashed by the build script.
+//! Use the [selector](crate::selector) macro to create an [EntryPoint](crate::EntryPoint)
+//! constant from one of those names without any runtime hashing.
+use stark_hash::Felt;
+
+include!(concat!(env!("OUT_DIR"), "/selectors.rs"));
+
+/// Returns the selector of the function `name`.
+///
+/// Panics if `name` is not one of the names listed in `build.rs`, which fails the build when
+/// evaluated in a `const` context.
+pub const fn lookup(name: &str) -> Felt {
+    let mut i = 0;
+    while i < SELECTORS.len() {
+        if bytes_eq(SELECTORS[i].0.as_bytes(), name.as_bytes()) {
+            return match Felt::from_be_bytes(SELECTORS[i].1) {
+                Ok(felt) => felt,
+                Err(_) => panic!("cannot overflow: smaller than modulus"),
+            };
+        }
+        i += 1;
+    }
+    panic!("unknown selector: add the function name to SELECTOR_NAMES in build.rs")
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Creates a function selector [EntryPoint](crate::EntryPoint) from a function name,
+/// computed at compile time.
+///
+/// The name must be listed in `SELECTOR_NAMES` in the `pathfinder-common` build script,
+/// otherwise the build fails.
+///
+/// ```
+/// use pathfinder_common::{selector, EntryPoint};
+///
+/// assert_eq!(selector!("constructor"), EntryPoint::hashed(b"constructor"));
+/// ```
+#[macro_export]
+macro_rules! selector {
+    ($name:expr) => {{
+        // This forces const evaluation of the macro call.
+        const SELECTOR: $crate::EntryPoint =
+            $crate::EntryPoint($crate::selector_registry::lookup($name));
+        SELECTOR
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EntryPoint;
+
+    #[test]
+    fn matches_runtime_hash() {
+        assert_eq!(
+            selector!("handle_deposit"),
+            EntryPoint::hashed(b"handle_deposit")
+        );
+
+        for (name, _) in SELECTORS {
+            assert_eq!(
+                EntryPoint(lookup(name)),
+                EntryPoint::hashed(name.as_bytes())
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown selector")]
+    fn unknown_name() {
+        lookup("not_registered");
+    }
+}
@@ -12,19 +12,29 @@
 use crate::metrics::{with_metrics, BlockTag, RequestMetadata};
 use pathfinder_common::{BlockId, ClassHash, TransactionHash};
 use starknet_gateway_types::error::SequencerError;
+use std::num::NonZeroUsize;
+use std::time::Duration;
 
 /// A Sequencer Request builder.
 pub struct Request<'a, S: RequestState> {
     state: S,
     url: reqwest::Url,
     client: &'a reqwest::Client,
+    timeout: Duration,
 }
 
 /// Describes the retry behavior of a [Request].
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
 pub enum Retry {
     Enabled,
     Disabled,
+    /// Retries only transient failures, for at most `max_attempts` attempts in total,
+    /// with a jittered exponential backoff starting at `base_delay`.
+    Bounded {
+        max_attempts: NonZeroUsize,
+        base_delay: Duration,
+    },
 }
 
 pub mod stage {
@@ -35,9 +45,11 @@ pub mod stage {
 
     /// Select the Sequencer API method to call:
     /// - [add_transaction](super::Request::add_transaction)
+    /// - [estimate_fee](super::Request::estimate_fee)
     /// - [get_block](super::Request::get_block)
     /// - [get_class_by_hash](super::Request::get_class_by_hash)
     /// - [get_transaction](super::Request::get_transaction)
+    /// - [get_transaction_trace](super::Request::get_transaction_trace)
     /// - [get_state_update](super::Request::get_state_update)
     /// - [get_contract_addresses](super::Request::get_contract_addresses)
     pub struct Method;
@@ -48,6 +60,7 @@ pub mod stage {
     /// - [with_optional_token](super::Request::with_optional_token)
     /// - [with_transaction_hash](super::Request::with_transaction_hash)
     /// - [add_param](super::Request::add_param) (allows adding custom (name, value) parameter)
+    /// - [with_timeout](super::Request::with_timeout)
     ///
     /// and then specify the [retry behavior](super::Request::with_retry).
     pub struct Params {
@@ -70,11 +83,17 @@ pub mod stage {
 }
 
 impl<'a> Request<'a, stage::Init> {
-    /// Initialize a [Request] builder.
-    pub fn builder(client: &'a reqwest::Client, url: reqwest::Url) -> Request<'a, stage::Method> {
+    /// Initialize a [Request] builder which times out after `timeout` unless
+    /// [overridden](Request::with_timeout).
+    pub fn builder(
+        client: &'a reqwest::Client,
+        url: reqwest::Url,
+        timeout: Duration,
+    ) -> Request<'a, stage::Method> {
         Request {
             url,
             client,
+            timeout,
             state: stage::Method,
         }
     }
@@ -130,9 +149,11 @@ mod request_macros {
 impl<'a> Request<'a, stage::Method> {
     request_macros::methods!(
         add_transaction,
+        estimate_fee,
         get_block,
         get_class_by_hash,
         get_transaction,
+        get_transaction_trace,
         get_state_update,
         get_contract_addresses,
     );
@@ -147,6 +168,7 @@ impl<'a> Request<'a, stage::Method> {
         Request {
             url: self.url,
             client: self.client,
+            timeout: self.timeout,
             state: stage::Params {
                 meta: RequestMetadata::new(method),
             },
@@ -199,11 +221,19 @@ impl<'a> Request<'a, stage::Params> {
         self
     }
 
+    /// Sets the time after which each attempt of the request fails with
+    /// [SequencerError::Timeout].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Sets the request retry behavior.
     pub fn with_retry(self, retry: Retry) -> Request<'a, stage::Final> {
         Request {
             url: self.url,
             client: self.client,
+            timeout: self.timeout,
             state: stage::Final {
                 meta: self.state.meta,
                 retry,
@@ -221,27 +251,45 @@ impl<'a> Request<'a, stage::Final> {
         async fn send_request<T: serde::de::DeserializeOwned>(
             url: reqwest::Url,
             client: &reqwest::Client,
+            timeout: Duration,
             meta: RequestMetadata,
         ) -> Result<T, SequencerError> {
             with_metrics(meta, async move {
-                let response = client.get(url).send().await?;
+                let response = client.get(url).timeout(timeout).send().await?;
                 parse::<T>(response).await
             })
             .await
         }
 
         match self.state.retry {
-            Retry::Disabled => send_request(self.url, self.client, self.state.meta).await,
+            Retry::Disabled => {
+                send_request(self.url, self.client, self.timeout, self.state.meta).await
+            }
             Retry::Enabled => {
                 retry0(
                     || async {
                         let clone_url = self.url.clone();
-                        send_request(clone_url, self.client, self.state.meta).await
+                        send_request(clone_url, self.client, self.timeout, self.state.meta).await
                     },
                     retry_condition,
                 )
                 .await
             }
+            Retry::Bounded {
+                max_attempts,
+                base_delay,
+            } => {
+                retry_bounded(
+                    || async {
+                        let clone_url = self.url.clone();
+                        send_request(clone_url, self.client, self.timeout, self.state.meta).await
+                    },
+                    max_attempts,
+                    base_delay,
+                    self.state.meta.method,
+                )
+                .await
+            }
         }
     }
 
@@ -250,10 +298,11 @@ impl<'a> Request<'a, stage::Final> {
         async fn get_as_bytes_inner(
             url: reqwest::Url,
             client: &reqwest::Client,
+            timeout: Duration,
             meta: RequestMetadata,
         ) -> Result<bytes::Bytes, SequencerError> {
             with_metrics(meta, async {
-                let response = client.get(url).send().await?;
+                let response = client.get(url).timeout(timeout).send().await?;
                 let response = parse_raw(response).await?;
                 let bytes = response.bytes().await?;
                 Ok(bytes)
@@ -262,17 +311,36 @@ impl<'a> Request<'a, stage::Final> {
         }
 
         match self.state.retry {
-            Retry::Disabled => get_as_bytes_inner(self.url, self.client, self.state.meta).await,
+            Retry::Disabled => {
+                get_as_bytes_inner(self.url, self.client, self.timeout, self.state.meta).await
+            }
             Retry::Enabled => {
                 retry0(
                     || async {
                         let clone_url = self.url.clone();
-                        get_as_bytes_inner(clone_url, self.client, self.state.meta).await
+                        get_as_bytes_inner(clone_url, self.client, self.timeout, self.state.meta)
+                            .await
                     },
                     retry_condition,
                 )
                 .await
             }
+            Retry::Bounded {
+                max_attempts,
+                base_delay,
+            } => {
+                retry_bounded(
+                    || async {
+                        let clone_url = self.url.clone();
+                        get_as_bytes_inner(clone_url, self.client, self.timeout, self.state.meta)
+                            .await
+                    },
+                    max_attempts,
+                    base_delay,
+                    self.state.meta.method,
+                )
+                .await
+            }
         }
     }
 
@@ -286,6 +354,7 @@ impl<'a> Request<'a, stage::Final> {
         async fn post_with_json_inner<T, J>(
             url: reqwest::Url,
             client: &reqwest::Client,
+            timeout: Duration,
             meta: RequestMetadata,
             json: &J,
         ) -> Result<T, SequencerError>
@@ -294,7 +363,7 @@ impl<'a> Request<'a, stage::Final> {
             J: serde::Serialize + ?Sized,
         {
             with_metrics(meta, async {
-                let response = client.post(url).json(json).send().await?;
+                let response = client.post(url).timeout(timeout).json(json).send().await?;
                 parse::<T>(response).await
             })
             .await
@@ -302,18 +371,48 @@ impl<'a> Request<'a, stage::Final> {
 
         match self.state.retry {
             Retry::Disabled => {
-                post_with_json_inner(self.url, self.client, self.state.meta, json).await
+                post_with_json_inner(self.url, self.client, self.timeout, self.state.meta, json)
+                    .await
             }
             Retry::Enabled => {
                 retry0(
                     || async {
                         let clone_url = self.url.clone();
-                        post_with_json_inner(clone_url, self.client, self.state.meta, json).await
+                        post_with_json_inner(
+                            clone_url,
+                            self.client,
+                            self.timeout,
+                            self.state.meta,
+                            json,
+                        )
+                        .await
                     },
                     retry_condition,
                 )
                 .await
             }
+            Retry::Bounded {
+                max_attempts,
+                base_delay,
+            } => {
+                retry_bounded(
+                    || async {
+                        let clone_url = self.url.clone();
+                        post_with_json_inner(
+                            clone_url,
+                            self.client,
+                            self.timeout,
+                            self.state.meta,
+                            json,
+                        )
+                        .await
+                    },
+                    max_attempts,
+                    base_delay,
+                    self.state.meta.method,
+                )
+                .await
+            }
         }
     }
 }
@@ -369,6 +468,87 @@ where
         .await
 }
 
+tokio::task_local! {
+    /// Number of retries made by [retry_bounded] within [count_retries].
+    static RETRIES: std::cell::Cell<usize>;
+}
+
+/// Awaits `future`, returning its output together with the number of retries made by
+/// the requests it awaited, see [Client::with_retry](crate::Client::with_retry).
+///
+/// Only requests awaited by `future` itself are counted, not those of tasks it spawns.
+/// A nested call counts the retries of its own future only.
+pub async fn count_retries<F: futures::Future>(future: F) -> (F::Output, usize) {
+    RETRIES
+        .scope(std::cell::Cell::new(0), async move {
+            let output = future.await;
+            (output, RETRIES.with(std::cell::Cell::get))
+        })
+        .await
+}
+
+/// Retries the future while it fails with a [transient](is_transient) error, for at most
+/// `max_attempts` attempts in total. The last error is returned unchanged once the attempts
+/// are exhausted.
+///
+/// The delay before retry `N` is `base_delay * 2^(N-1)`, saturating at 10 minutes, scaled
+/// by a random factor in `[0.5, 1]` so that clients do not retry in lockstep. Every retry
+/// increments the `gateway_requests_retried_total` counter of the `method`, and is counted
+/// by an enclosing [count_retries].
+async fn retry_bounded<T, Fut, FutureFactory>(
+    mut future_factory: FutureFactory,
+    max_attempts: NonZeroUsize,
+    base_delay: Duration,
+    method: &'static str,
+) -> Result<T, SequencerError>
+where
+    Fut: futures::Future<Output = Result<T, SequencerError>>,
+    FutureFactory: FnMut() -> Fut,
+{
+    use rand::Rng;
+
+    const MAX_DELAY: Duration = Duration::from_secs(10 * 60);
+
+    let mut attempt = 1;
+    loop {
+        match future_factory().await {
+            Err(e) if attempt < max_attempts.get() && is_transient(&e) => {
+                let backoff = 1u32
+                    .checked_shl(attempt as u32 - 1)
+                    .map_or(MAX_DELAY, |factor| base_delay.saturating_mul(factor))
+                    .min(MAX_DELAY);
+                let delay = backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0));
+
+                tracing::debug!(reason=%e, %method, %attempt, ?delay, "Request failed, retrying");
+                crate::metrics::increment_retries(method);
+                // Not within count_retries otherwise, in which case there is nothing to count.
+                let _ = RETRIES.try_with(|retries| retries.set(retries.get() + 1));
+
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Transport failures, rate limiting and server errors other than Starknet errors are transient.
+/// Any other client error is not.
+fn is_transient(e: &SequencerError) -> bool {
+    match e {
+        SequencerError::ReqwestError(e) => match e.status() {
+            Some(status) => {
+                status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            None => e.is_connect() || e.is_request() || e.is_body(),
+        },
+        SequencerError::Timeout(_) => true,
+        // A 500 response without a Starknet error in its body.
+        SequencerError::InvalidStarknetErrorVariant => true,
+        SequencerError::StarknetError(_) => false,
+    }
+}
+
 /// Determines if an error is retryable or not.
 fn retry_condition(e: &SequencerError) -> bool {
     use reqwest::StatusCode;
@@ -376,7 +556,7 @@ fn retry_condition(e: &SequencerError) -> bool {
 
     match e {
         SequencerError::ReqwestError(e) => {
-            if e.is_body() || e.is_connect() || e.is_timeout() {
+            if e.is_body() || e.is_connect() {
                 info!(reason=%e, "Request failed, retrying");
             } else if e.is_status() {
                 match e.status() {
@@ -403,6 +583,10 @@ fn retry_condition(e: &SequencerError) -> bool {
 
             true
         }
+        SequencerError::Timeout(e) => {
+            info!(reason=%e, "Request failed, retrying");
+            true
+        }
         SequencerError::StarknetError(_) => false,
         SequencerError::InvalidStarknetErrorVariant => {
             error!(reason=%e, "Request failed, retrying");
@@ -421,7 +605,7 @@ mod tests {
         use tokio::{sync::Mutex, task::JoinHandle};
         use warp::Filter;
 
-        use crate::builder::{retry0, retry_condition};
+        use crate::builder::{count_retries, retry0, retry_bounded, retry_condition};
 
         // A test helper
         fn status_queue_server(
@@ -567,6 +751,128 @@ mod tests {
             // 5th try should have timedout if this is really exponential backoff
             assert_eq!(CNT.load(Ordering::Relaxed), 5);
         }
+
+        mod bounded {
+            use super::*;
+            use crate::builder;
+            use pretty_assertions::assert_eq;
+            use starknet_gateway_types::error::SequencerError;
+            use std::num::NonZeroUsize;
+            use std::sync::atomic::{AtomicUsize, Ordering};
+
+            /// Requests `statuses` in order with a bounded retry, returning the result and
+            /// number of attempts made. Also checks that every attempt but the first was
+            /// counted as a retry.
+            async fn run(
+                statuses: VecDeque<(StatusCode, &'static str)>,
+                max_attempts: usize,
+            ) -> (Result<String, SequencerError>, usize) {
+                tokio::time::pause();
+
+                let (_jh, addr) = status_queue_server(statuses);
+                let attempts = AtomicUsize::new(0);
+
+                let (result, retries) = count_retries(retry_bounded(
+                    || async {
+                        attempts.fetch_add(1, Ordering::Relaxed);
+                        let mut url = reqwest::Url::parse("http://localhost/").unwrap();
+                        url.set_port(Some(addr.port())).unwrap();
+                        let response = reqwest::get(url).await?;
+                        builder::parse::<String>(response).await
+                    },
+                    NonZeroUsize::new(max_attempts).unwrap(),
+                    Duration::from_millis(100),
+                    "test",
+                ))
+                .await;
+
+                let attempts = attempts.into_inner();
+                assert_eq!(retries, attempts - 1);
+                (result, attempts)
+            }
+
+            #[test_log::test(tokio::test)]
+            async fn retries_transient_errors() {
+                let statuses = VecDeque::from([
+                    (StatusCode::TOO_MANY_REQUESTS, ""),
+                    (StatusCode::BAD_GATEWAY, ""),
+                    (StatusCode::INTERNAL_SERVER_ERROR, "not a starknet error"),
+                    (StatusCode::OK, r#""Finally!""#),
+                ]);
+
+                let (result, attempts) = run(statuses, 5).await;
+                assert_eq!(result.unwrap(), "Finally!");
+                assert_eq!(attempts, 4);
+            }
+
+            #[test_log::test(tokio::test)]
+            async fn does_not_retry_client_errors() {
+                let statuses = VecDeque::from([
+                    (StatusCode::NOT_FOUND, ""),
+                    (StatusCode::OK, r#""Finally!""#),
+                ]);
+
+                let (result, attempts) = run(statuses, 5).await;
+                assert_matches!(
+                    result.unwrap_err(),
+                    SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(StatusCode::NOT_FOUND))
+                );
+                assert_eq!(attempts, 1);
+            }
+
+            #[test_log::test(tokio::test)]
+            async fn counts_retries_per_call() {
+                use crate::{Client, GatewayApi};
+                use pathfinder_common::BlockId;
+
+                tokio::time::pause();
+
+                let statuses = VecDeque::from([
+                    (StatusCode::SERVICE_UNAVAILABLE, ""),
+                    (StatusCode::SERVICE_UNAVAILABLE, ""),
+                    (StatusCode::SERVICE_UNAVAILABLE, ""),
+                    (StatusCode::NOT_FOUND, ""),
+                ]);
+                let (_jh, addr) = status_queue_server(statuses);
+                let mut url = reqwest::Url::parse("http://localhost/").unwrap();
+                url.set_port(Some(addr.port())).unwrap();
+                let client = Client::with_base_url(url)
+                    .unwrap()
+                    .with_retry(NonZeroUsize::new(3).unwrap(), Duration::from_millis(100));
+
+                let (result, retries) = count_retries(client.block(BlockId::Latest)).await;
+                assert_matches!(
+                    result.unwrap_err(),
+                    SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(StatusCode::SERVICE_UNAVAILABLE))
+                );
+                assert_eq!(retries, 2);
+
+                // The next call starts counting from zero.
+                let (result, retries) = count_retries(client.block(BlockId::Latest)).await;
+                assert_matches!(
+                    result.unwrap_err(),
+                    SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(StatusCode::NOT_FOUND))
+                );
+                assert_eq!(retries, 0);
+            }
+
+            #[test_log::test(tokio::test)]
+            async fn returns_last_error_when_exhausted() {
+                let statuses = VecDeque::from([
+                    (StatusCode::SERVICE_UNAVAILABLE, ""),
+                    (StatusCode::SERVICE_UNAVAILABLE, ""),
+                    (StatusCode::GATEWAY_TIMEOUT, ""),
+                    (StatusCode::OK, r#""Finally!""#),
+                ]);
+
+                let (result, attempts) = run(statuses, 3).await;
+                assert_matches!(
+                    result.unwrap_err(),
+                    SequencerError::ReqwestError(e) => assert_eq!(e.status(), Some(StatusCode::GATEWAY_TIMEOUT))
+                );
+                assert_eq!(attempts, 3);
+            }
+        }
     }
 
     mod invalid_starknet_error_variant {
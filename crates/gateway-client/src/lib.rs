@@ -12,8 +12,9 @@ use starknet_gateway_types::{
         AddTransaction, ContractDefinition, Declare, DeployAccount, InvokeFunction,
     },
 };
-use std::{fmt::Debug, result::Result, time::Duration};
+use std::{fmt::Debug, num::NonZeroUsize, result::Result, time::Duration};
 
+pub use crate::builder::count_retries;
 use crate::builder::Retry;
 
 mod builder;
@@ -52,6 +53,13 @@ pub trait GatewayApi: Sync {
         unimplemented!();
     }
 
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> Result<reply::TransactionTrace, SequencerError> {
+        unimplemented!();
+    }
+
     async fn state_update(
         &self,
         block: BlockId,
@@ -63,6 +71,14 @@ pub trait GatewayApi: Sync {
         unimplemented!();
     }
 
+    async fn estimate_fee(
+        &self,
+        transaction: AddTransaction,
+        block: BlockId,
+    ) -> Result<reply::FeeEstimate, SequencerError> {
+        unimplemented!();
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn add_invoke_transaction(
         &self,
@@ -116,6 +132,10 @@ pub trait GatewayApi: Sync {
 /// `backoff [secs] = min((2 ^ N) * 15, 600) [secs]`
 ///
 /// where `N` is the consecutive retry iteration number `{1, 2, ...}`.
+///
+/// A bounded retry behavior can be configured instead using [Client::with_retry].
+///
+/// Requests time out according to the client's [ClientTimeouts], see [Client::with_timeouts].
 #[derive(Debug, Clone)]
 pub struct Client {
     /// This client is internally refcounted
@@ -124,6 +144,31 @@ pub struct Client {
     gateway: Url,
     /// Starknet feeder gateway URL.
     feeder_gateway: Url,
+    /// Retry behavior of requests which are retried.
+    retry: Retry,
+    timeouts: ClientTimeouts,
+}
+
+/// Timeouts of the requests made by a [Client].
+///
+/// Each attempt of a request which does not complete in time fails with
+/// [SequencerError::Timeout].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientTimeouts {
+    /// Timeout of requests which read data, such as blocks or transactions, and of requests
+    /// which add transactions.
+    pub read: Duration,
+    /// Timeout of requests which execute a transaction, such as [GatewayApi::estimate_fee].
+    pub compute: Duration,
+}
+
+impl Default for ClientTimeouts {
+    fn default() -> Self {
+        Self {
+            read: Duration::from_secs(120),
+            compute: Duration::from_secs(120),
+        }
+    }
 }
 
 impl Client {
@@ -178,20 +223,42 @@ impl Client {
 
         Ok(Self {
             inner: reqwest::Client::builder()
-                .timeout(Duration::from_secs(120))
                 .user_agent(pathfinder_common::consts::USER_AGENT)
                 .build()?,
             gateway,
             feeder_gateway,
+            retry: Self::RETRY,
+            timeouts: ClientTimeouts::default(),
         })
     }
 
+    /// Replaces the default retry behavior with at most `max_attempts` attempts per request.
+    ///
+    /// Only transport errors, rate limiting and server errors are retried, using exponential
+    /// backoff with jitter starting at `base_delay`. Once the attempts are exhausted the last
+    /// error is returned. Requests which are never retried are not affected. The number of
+    /// retries made for a call can be observed with [count_retries].
+    pub fn with_retry(self, max_attempts: NonZeroUsize, base_delay: Duration) -> Self {
+        Self {
+            retry: Retry::Bounded {
+                max_attempts,
+                base_delay,
+            },
+            ..self
+        }
+    }
+
+    /// Replaces the default [ClientTimeouts].
+    pub fn with_timeouts(self, timeouts: ClientTimeouts) -> Self {
+        Self { timeouts, ..self }
+    }
+
     fn gateway_request(&self) -> builder::Request<'_, builder::stage::Method> {
-        builder::Request::builder(&self.inner, self.gateway.clone())
+        builder::Request::builder(&self.inner, self.gateway.clone(), self.timeouts.read)
     }
 
     fn feeder_gateway_request(&self) -> builder::Request<'_, builder::stage::Method> {
-        builder::Request::builder(&self.inner, self.feeder_gateway.clone())
+        builder::Request::builder(&self.inner, self.feeder_gateway.clone(), self.timeouts.read)
     }
 
     async fn block_with_retry_behaviour(
@@ -229,13 +296,60 @@ impl Client {
             other => Err(anyhow::anyhow!("Unknown genesis block hash: {}", other.0)),
         }
     }
+
+    /// Fetches all blocks in `start..=end`, with at most `concurrency` requests in flight.
+    ///
+    /// The blocks are returned in ascending order without gaps. Fails on the first
+    /// error encountered, including a pending or mismatched block being returned.
+    pub async fn blocks_by_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+        concurrency: usize,
+    ) -> anyhow::Result<Vec<reply::Block>> {
+        use anyhow::Context;
+        use tokio::sync::Semaphore;
+
+        anyhow::ensure!(concurrency > 0, "Concurrency must be at least one");
+
+        // Requests wait for a permit before being sent. The semaphore is fair, so they are
+        // sent in ascending order.
+        let semaphore = Semaphore::new(concurrency.min(Semaphore::MAX_PERMITS));
+
+        let requests = (start.get()..=end.get()).map(|number| {
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore
+                    .acquire()
+                    .await
+                    .expect("Semaphore is never closed");
+
+                let number = BlockNumber::new_or_panic(number);
+                let block = self
+                    .block(number.into())
+                    .await
+                    .with_context(|| format!("Fetching block {number}"))?
+                    .as_block()
+                    .with_context(|| format!("Block {number} is pending"))?;
+                anyhow::ensure!(
+                    block.block_number == number,
+                    "Requested block {number} but received block {}",
+                    block.block_number
+                );
+                Ok(block)
+            }
+        });
+
+        // Preserves the order of the requests, so the output is sorted by block number.
+        futures::future::try_join_all(requests).await
+    }
 }
 
 #[async_trait::async_trait]
 impl GatewayApi for Client {
     #[tracing::instrument(skip(self))]
     async fn block(&self, block: BlockId) -> Result<reply::MaybePendingBlock, SequencerError> {
-        self.block_with_retry_behaviour(block, Self::RETRY).await
+        self.block_with_retry_behaviour(block, self.retry).await
     }
 
     #[tracing::instrument(skip(self))]
@@ -253,7 +367,7 @@ impl GatewayApi for Client {
         self.feeder_gateway_request()
             .get_class_by_hash()
             .with_class_hash(class_hash)
-            .with_retry(Self::RETRY)
+            .with_retry(self.retry)
             .get_as_bytes()
             .await
     }
@@ -268,7 +382,7 @@ impl GatewayApi for Client {
             .get_class_by_hash()
             .with_class_hash(class_hash)
             .with_block(BlockId::Pending)
-            .with_retry(Self::RETRY)
+            .with_retry(self.retry)
             .get_as_bytes()
             .await
     }
@@ -282,7 +396,21 @@ impl GatewayApi for Client {
         self.feeder_gateway_request()
             .get_transaction()
             .with_transaction_hash(transaction_hash)
-            .with_retry(Self::RETRY)
+            .with_retry(self.retry)
+            .get()
+            .await
+    }
+
+    /// Gets the trace of the calls made while executing a transaction.
+    #[tracing::instrument(skip(self))]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> Result<reply::TransactionTrace, SequencerError> {
+        self.feeder_gateway_request()
+            .get_transaction_trace()
+            .with_transaction_hash(transaction_hash)
+            .with_retry(self.retry)
             .get()
             .await
     }
@@ -295,7 +423,7 @@ impl GatewayApi for Client {
         self.feeder_gateway_request()
             .get_state_update()
             .with_block(block)
-            .with_retry(Self::RETRY)
+            .with_retry(self.retry)
             .get()
             .await
     }
@@ -305,11 +433,29 @@ impl GatewayApi for Client {
     async fn eth_contract_addresses(&self) -> Result<reply::EthContractAddresses, SequencerError> {
         self.feeder_gateway_request()
             .get_contract_addresses()
-            .with_retry(Self::RETRY)
+            .with_retry(self.retry)
             .get()
             .await
     }
 
+    /// Estimates the fee of a transaction, executed on top of the given block.
+    ///
+    /// A failed execution is reported as a [StarknetError](starknet_gateway_types::error::StarknetError).
+    #[tracing::instrument(skip(self))]
+    async fn estimate_fee(
+        &self,
+        transaction: AddTransaction,
+        block: BlockId,
+    ) -> Result<reply::FeeEstimate, SequencerError> {
+        self.feeder_gateway_request()
+            .estimate_fee()
+            .with_block(block)
+            .with_timeout(self.timeouts.compute)
+            .with_retry(self.retry)
+            .post_with_json(&transaction)
+            .await
+    }
+
     /// Adds a transaction invoking a contract.
     #[tracing::instrument(skip(self))]
     async fn add_invoke_transaction(
@@ -705,6 +851,80 @@ mod tests {
         }
     }
 
+    mod blocks_by_range {
+        use super::*;
+
+        #[tokio::test]
+        async fn ordered() {
+            let (_jh, client) = setup([
+                (
+                    "/feeder_gateway/get_block?blockNumber=0",
+                    (v0_9_0::block::GENESIS, 200),
+                ),
+                (
+                    "/feeder_gateway/get_block?blockNumber=1",
+                    (integration::block::NUMBER_1, 200),
+                ),
+            ]);
+            let blocks = client
+                .blocks_by_range(BlockNumber::GENESIS, BlockNumber::new_or_panic(1), 2)
+                .await
+                .unwrap();
+            let numbers = blocks.iter().map(|b| b.block_number).collect::<Vec<_>>();
+            assert_eq!(
+                numbers,
+                vec![BlockNumber::GENESIS, BlockNumber::new_or_panic(1)]
+            );
+        }
+
+        #[tokio::test]
+        async fn empty_range() {
+            let (_jh, client) = setup::<String, String, 0>([]);
+            let blocks = client
+                .blocks_by_range(BlockNumber::new_or_panic(1), BlockNumber::GENESIS, 1)
+                .await
+                .unwrap();
+            assert!(blocks.is_empty());
+        }
+
+        #[tokio::test]
+        async fn mismatched_block() {
+            let (_jh, client) = setup([(
+                "/feeder_gateway/get_block?blockNumber=1",
+                (v0_9_0::block::GENESIS, 200),
+            )]);
+            client
+                .blocks_by_range(
+                    BlockNumber::new_or_panic(1),
+                    BlockNumber::new_or_panic(1),
+                    1,
+                )
+                .await
+                .unwrap_err();
+        }
+
+        #[tokio::test]
+        async fn zero_concurrency() {
+            let (_jh, client) = setup::<String, String, 0>([]);
+            client
+                .blocks_by_range(BlockNumber::GENESIS, BlockNumber::GENESIS, 0)
+                .await
+                .unwrap_err();
+        }
+
+        #[tokio::test]
+        async fn error_short_circuits() {
+            let (_jh, client) = setup([(
+                "/feeder_gateway/get_block?blockNumber=0",
+                response_from(StarknetErrorCode::BlockNotFound),
+            )]);
+            client
+                .blocks_by_range(BlockNumber::GENESIS, BlockNumber::new_or_panic(1), 1)
+                .await
+                .unwrap_err();
+        }
+    }
+
     mod block {
         use super::*;
         use pathfinder_common::BlockId;
@@ -1074,6 +1294,128 @@ mod tests {
         client.eth_contract_addresses().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn trace_transaction() {
+        let (_jh, client) = setup([(
+            "/feeder_gateway/get_transaction_trace?transactionHash=0x123",
+            (v0_11_0::transaction_trace::INVOKE_V1, 200),
+        )]);
+        let trace = client
+            .trace_transaction(TransactionHash(felt!("0x123")))
+            .await
+            .unwrap();
+
+        let invocation = trace.function_invocation.unwrap();
+        assert_eq!(invocation.iter().count(), 4);
+        assert_eq!(trace.signature.len(), 4);
+    }
+
+    mod estimate_fee {
+        use super::*;
+
+        pub(super) fn transaction() -> AddTransaction {
+            AddTransaction::Invoke(InvokeFunction {
+                sender_address: ContractAddress::new_or_panic(felt!(
+                    "0x023371b227eaecd8e8920cd429357edddd2cd0f3fee6abaacca08d3ab82a7cdd"
+                )),
+                calldata: vec![CallParam(felt!("0x1")), CallParam(felt!("0x2b"))],
+                max_fee: Fee(Felt::ZERO),
+                version: TransactionVersion::ONE,
+                signature: vec![],
+                nonce: TransactionNonce(felt!("0x1")),
+            })
+        }
+
+        #[tokio::test]
+        async fn success() {
+            let (_jh, client) = setup([(
+                "/feeder_gateway/estimate_fee?blockNumber=latest",
+                (
+                    r#"{"overall_fee": 7820000000000, "unit": "wei", "gas_price": 100000000000, "gas_usage": 78}"#,
+                    200,
+                ),
+            )]);
+            let estimate = client
+                .estimate_fee(transaction(), BlockId::Latest)
+                .await
+                .unwrap();
+            assert_eq!(estimate.overall_fee.as_u64(), 7820000000000);
+            assert_eq!(estimate.gas_price.as_u64(), 100000000000);
+            assert_eq!(estimate.gas_usage.as_u64(), 78);
+        }
+
+        #[tokio::test]
+        async fn failure() {
+            let (_jh, client) = setup([(
+                "/feeder_gateway/estimate_fee?blockNumber=latest",
+                response_from(StarknetErrorCode::TransactionFailed),
+            )]);
+            let error = client
+                .estimate_fee(transaction(), BlockId::Latest)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::TransactionFailed)
+            );
+        }
+    }
+
+    mod timeouts {
+        use super::*;
+
+        /// Creates a [Client] for a server which responds to every request with a fee
+        /// estimate after 200ms.
+        fn slow_client(timeouts: ClientTimeouts) -> (tokio::task::JoinHandle<()>, Client) {
+            use warp::Filter;
+
+            let any = warp::any().then(|| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                http::response::Builder::new().status(200).body(
+                    r#"{"overall_fee": 7820000000000, "unit": "wei", "gas_price": 100000000000, "gas_usage": 78}"#,
+                )
+            });
+            let (addr, serve_fut) = warp::serve(any).bind_ephemeral(([127, 0, 0, 1], 0));
+            let server_handle = tokio::spawn(serve_fut);
+
+            let url = Url::parse(&format!("http://{addr}")).unwrap();
+            let client = Client::with_base_url(url).unwrap().with_timeouts(timeouts);
+            (server_handle, client)
+        }
+
+        #[tokio::test]
+        async fn per_endpoint() {
+            let (_jh, client) = slow_client(ClientTimeouts {
+                read: Duration::from_millis(10),
+                compute: Duration::from_secs(10),
+            });
+
+            let error = client.block(BlockId::Latest).await.unwrap_err();
+            assert_matches!(error, SequencerError::Timeout(_));
+
+            let transaction = estimate_fee::transaction();
+            client
+                .estimate_fee(transaction, BlockId::Latest)
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn compute() {
+            let (_jh, client) = slow_client(ClientTimeouts {
+                read: Duration::from_secs(10),
+                compute: Duration::from_millis(10),
+            });
+
+            let transaction = estimate_fee::transaction();
+            let error = client
+                .estimate_fee(transaction, BlockId::Latest)
+                .await
+                .unwrap_err();
+            assert_matches!(error, SequencerError::Timeout(_));
+        }
+    }
+
     mod add_transaction {
         use super::*;
         use pathfinder_common::{felt, ByteCodeOffset, ContractAddress};
@@ -8,6 +8,7 @@ use pathfinder_common::BlockId;
 
 const METRIC_REQUESTS: &str = "gateway_requests_total";
 const METRIC_FAILED_REQUESTS: &str = "gateway_requests_failed_total";
+const METRIC_RETRIED_REQUESTS: &str = "gateway_requests_retried_total";
 const METRICS: [&str; 2] = [METRIC_REQUESTS, METRIC_FAILED_REQUESTS];
 const TAG_LATEST: &str = "latest";
 const TAG_PENDING: &str = "pending";
@@ -36,6 +37,11 @@ pub fn register() {
         })
     });
 
+    // Retries performed by bounded retry requests
+    Request::<'_, Method>::METHODS.iter().for_each(|&method| {
+        metrics::register_counter!(METRIC_RETRIED_REQUESTS, "method" => method);
+    });
+
     // Failed requests for specific failure reasons
     REASONS.iter().for_each(|&reason| {
         // For all methods
@@ -163,9 +169,14 @@ pub async fn with_metrics<T>(
             {
                 increment_failed(meta, REASON_RATE_LIMITING);
             }
-            SequencerError::ReqwestError(_) => {}
+            SequencerError::ReqwestError(_) | SequencerError::Timeout(_) => {}
         }
 
         e
     })
 }
+
+/// Increments the `gateway_requests_retried_total` counter for a particular method.
+pub fn increment_retries(method: &'static str) {
+    metrics::increment_counter!(METRIC_RETRIED_REQUESTS, "method" => method);
+}
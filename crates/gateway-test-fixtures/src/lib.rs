@@ -36,6 +36,8 @@ pub mod v0_9_0 {
         pub const NUMBER_156000: &str = str_fixture!("0.9.0/block/156000.json");
         pub const NUMBER_231579: &str = str_fixture!("0.9.0/block/231579.json");
         pub const PENDING: &str = str_fixture!("0.9.0/block/pending.json");
+        /// Genesis block with the `transactions` and `transaction_receipts` keys removed.
+        pub const TRANSACTIONS_ABSENT: &str = str_fixture!("0.9.0/block/transactions_absent.json");
     }
 
     pub mod transaction {
@@ -62,6 +64,13 @@ pub mod v0_11_0 {
         pub const PENDING: &str = str_fixture!("0.11.0/state-update/pending.json");
     }
 
+    pub mod transaction_trace {
+        /// Trace of an account's invoke v1 transaction transferring ETH, as serialized by
+        /// cairo-lang's `TransactionTrace` schema. Taken from the trace cairo-lang returned
+        /// for the `ext_py` fixture `crates/rpc/fixtures/ext_py/tx_traces.json`.
+        pub const INVOKE_V1: &str = str_fixture!("0.11.0/transaction-trace/invoke_v1.json");
+    }
+
     /// Some of the following transactions are "as of" 0.11.0 and not really
     /// introduced in the chain in 0.11.0
     pub mod transaction {
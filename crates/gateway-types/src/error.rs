@@ -9,13 +9,26 @@ pub enum SequencerError {
     StarknetError(#[from] StarknetError),
     /// Errors directly coming from reqwest
     #[error(transparent)]
-    ReqwestError(#[from] reqwest::Error),
+    ReqwestError(reqwest::Error),
+    /// The request did not complete within its timeout.
+    #[error(transparent)]
+    Timeout(reqwest::Error),
     /// Custom errors that we fidded with because the original error was either
     /// not informative enough or bloated
     #[error("error decoding response body: invalid error variant")]
     InvalidStarknetErrorVariant,
 }
 
+impl From<reqwest::Error> for SequencerError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Timeout(e)
+        } else {
+            Self::ReqwestError(e)
+        }
+    }
+}
+
 /// Used for deserializing specific Starknet sequencer error data.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct StarknetError {
@@ -1,9 +1,10 @@
 //! Structures used for deserializing replies from Starkware's sequencer REST API.
+use ethers::types::U256;
 use pathfinder_common::{
     BlockHash, BlockNumber, BlockTimestamp, EthereumAddress, GasPrice, SequencerAddress,
-    StarknetVersion, StateCommitment,
+    StarknetVersion, StateCommitment, TransactionHash, TransactionSignatureElem,
 };
-use pathfinder_serde::{EthereumAddressAsHexStr, GasPriceAsHexStr};
+use pathfinder_serde::{EthereumAddressAsHexStr, GasPriceAsHexStr, U256AsBigDecimal};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
@@ -27,7 +28,13 @@ pub struct Block {
     pub state_commitment: StateCommitment,
     pub status: Status,
     pub timestamp: BlockTimestamp,
+    /// The sequencer occasionally omits this key instead of returning an empty array,
+    /// see [Block::validate_receipts_match_transactions].
+    #[serde(default)]
     pub transaction_receipts: Vec<transaction::Receipt>,
+    /// The sequencer occasionally omits this key instead of returning an empty array,
+    /// see [Block::validate_receipts_match_transactions].
+    #[serde(default)]
     pub transactions: Vec<transaction::Transaction>,
     /// Version metadata introduced in 0.9.1, older blocks will not have it.
     #[serde(default)]
@@ -59,6 +66,124 @@ pub enum MaybePendingBlock {
     Pending(PendingBlock),
 }
 
+impl Block {
+    /// Returns the block's transactions keyed by their hash.
+    pub fn transactions_by_hash(
+        &self,
+    ) -> std::collections::HashMap<TransactionHash, &transaction::Transaction> {
+        self.transactions.iter().map(|tx| (tx.hash(), tx)).collect()
+    }
+
+    /// Number of transactions in the block.
+    pub fn transaction_count(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Total number of events emitted by the block's transactions.
+    pub fn event_count(&self) -> usize {
+        self.transaction_receipts
+            .iter()
+            .map(|receipt| receipt.events.len())
+            .sum()
+    }
+
+    /// Total number of L2 to L1 messages sent by the block's transactions.
+    pub fn l2_to_l1_message_count(&self) -> usize {
+        self.transaction_receipts
+            .iter()
+            .map(|receipt| receipt.l2_to_l1_messages.len())
+            .sum()
+    }
+
+    /// Checks that there is exactly one receipt per transaction. This catches only one of
+    /// the two arrays being omitted by the sequencer, which would otherwise go unnoticed as
+    /// missing arrays default to empty.
+    pub fn validate_receipts_match_transactions(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.transactions.len() == self.transaction_receipts.len(),
+            "Block {} has {} transactions but {} receipts",
+            self.block_number,
+            self.transactions.len(),
+            self.transaction_receipts.len()
+        );
+        Ok(())
+    }
+
+    /// See [Status::is_pending].
+    pub fn is_pending(&self) -> bool {
+        self.status.is_pending()
+    }
+
+    /// See [Status::is_accepted_on_l2].
+    pub fn is_accepted_on_l2(&self) -> bool {
+        self.status.is_accepted_on_l2()
+    }
+
+    /// See [Status::is_accepted_on_l1].
+    pub fn is_accepted_on_l1(&self) -> bool {
+        self.status.is_accepted_on_l1()
+    }
+
+    /// See [Status::is_finalized].
+    pub fn is_finalized(&self) -> bool {
+        self.status.is_finalized()
+    }
+
+    /// See [Status::is_failed].
+    pub fn is_failed(&self) -> bool {
+        self.status.is_failed()
+    }
+}
+
+/// Summary of how busy a [Block] was, e.g. for logging.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockStats {
+    pub transaction_count: usize,
+    pub event_count: usize,
+    /// Total Cairo steps of all transactions, saturating at [u64::MAX].
+    pub n_steps: u64,
+    /// Total builtin instances used by all transactions, saturating at [u64::MAX]. Receipts
+    /// without execution resources or builtin counts don't contribute.
+    pub builtins: transaction::execution_resources::NormalBuiltinInstanceCounter,
+}
+
+impl From<&Block> for BlockStats {
+    fn from(block: &Block) -> Self {
+        let mut stats = BlockStats {
+            transaction_count: block.transaction_count(),
+            event_count: block.event_count(),
+            ..Default::default()
+        };
+
+        let resources = block
+            .transaction_receipts
+            .iter()
+            .filter_map(|receipt| receipt.execution_resources);
+
+        for resources in resources {
+            stats.n_steps = stats.n_steps.saturating_add(resources.n_steps);
+
+            if let Some(counter) = resources.builtin_instance_counter.normal() {
+                let total = &mut stats.builtins;
+                total.bitwise_builtin = total
+                    .bitwise_builtin
+                    .saturating_add(counter.bitwise_builtin);
+                total.ecdsa_builtin = total.ecdsa_builtin.saturating_add(counter.ecdsa_builtin);
+                total.ec_op_builtin = total.ec_op_builtin.saturating_add(counter.ec_op_builtin);
+                total.output_builtin = total.output_builtin.saturating_add(counter.output_builtin);
+                total.pedersen_builtin = total
+                    .pedersen_builtin
+                    .saturating_add(counter.pedersen_builtin);
+                total.range_check_builtin = total
+                    .range_check_builtin
+                    .saturating_add(counter.range_check_builtin);
+            }
+        }
+
+        stats
+    }
+}
+
 impl From<Block> for MaybePendingBlock {
     fn from(block: Block) -> Self {
         MaybePendingBlock::Block(block)
@@ -99,6 +224,11 @@ impl MaybePendingBlock {
             MaybePendingBlock::Pending(p) => p.status,
         }
     }
+
+    /// Returns `true` for the pending block, which does not have a number or hash yet.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, MaybePendingBlock::Pending(p) if p.status.is_pending())
+    }
 }
 
 /// Block and transaction status values.
@@ -138,20 +268,104 @@ impl std::fmt::Display for Status {
     }
 }
 
+impl Status {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Status::Pending)
+    }
+
+    pub fn is_accepted_on_l2(&self) -> bool {
+        matches!(self, Status::AcceptedOnL2)
+    }
+
+    pub fn is_accepted_on_l1(&self) -> bool {
+        matches!(self, Status::AcceptedOnL1)
+    }
+
+    /// Accepted on either L2 or L1.
+    pub fn is_finalized(&self) -> bool {
+        self.is_accepted_on_l2() || self.is_accepted_on_l1()
+    }
+
+    /// Rejected, reverted or aborted.
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Status::Rejected | Status::Reverted | Status::Aborted)
+    }
+
+    /// Accepted on L1 or [failed](Status::is_failed), after which the status no longer changes.
+    pub fn is_terminal(&self) -> bool {
+        self.is_accepted_on_l1() || self.is_failed()
+    }
+
+    /// Whether the status can later change to `other`.
+    ///
+    /// Statuses progress from `NOT_RECEIVED` through `RECEIVED`, `PENDING` and `ACCEPTED_ON_L2`
+    /// to `ACCEPTED_ON_L1`, possibly skipping some of them, see [PartialOrd]. Any status which
+    /// is not [terminal](Status::is_terminal) can also fail instead.
+    pub fn precedes(&self, other: Status) -> bool {
+        !self.is_terminal() && (other.is_failed() || *self < other)
+    }
+
+    /// Position along the successful progression of statuses, [None] if failed.
+    fn progress(&self) -> Option<u8> {
+        match self {
+            Status::NotReceived => Some(0),
+            Status::Received => Some(1),
+            Status::Pending => Some(2),
+            Status::AcceptedOnL2 => Some(3),
+            Status::AcceptedOnL1 => Some(4),
+            Status::Rejected | Status::Reverted | Status::Aborted => None,
+        }
+    }
+}
+
+/// Orders statuses by their progress, i.e. `NOT_RECEIVED < RECEIVED < PENDING < ACCEPTED_ON_L2 <
+/// ACCEPTED_ON_L1`.
+///
+/// [Failed](Status::is_failed) statuses are only comparable to themselves. This is also why
+/// there is no [Ord] implementation.
+impl PartialOrd for Status {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self.progress(), other.progress()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ if self == other => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
 /// Types used when deserializing L2 call related data.
 pub mod call {
-    use serde::Deserialize;
+    use serde::{Deserialize, Serialize};
     use serde_with::serde_as;
     use std::collections::HashMap;
 
     /// Describes problems encountered during some of call failures .
     #[serde_as]
-    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
     pub struct Problems {
         #[serde_as(as = "HashMap<_, _>")]
         pub calldata: HashMap<u64, Vec<String>>,
     }
+
+    impl Problems {
+        /// Returns the first error message of the calldata element with the lowest index.
+        pub fn first_error(&self) -> Option<&str> {
+            self.all_errors().into_iter().next()
+        }
+
+        /// Returns all error messages ordered by the index of their calldata element.
+        pub fn all_errors(&self) -> Vec<&str> {
+            let mut indices = self.calldata.keys().collect::<Vec<_>>();
+            indices.sort_unstable();
+
+            indices
+                .into_iter()
+                .flat_map(|index| &self.calldata[index])
+                .map(String::as_str)
+                .collect()
+        }
+    }
 }
 
 /// Used to deserialize replies to Starknet transaction requests.
@@ -184,6 +398,7 @@ pub struct TransactionStatus {
 
 /// Types used when deserializing L2 transaction related data.
 pub mod transaction {
+    use ethers::types::{H256, U256};
     use pathfinder_common::{
         CallParam, CasmHash, ClassHash, ConstructorParam, ContractAddress, ContractAddressSalt,
         EntryPoint, EthereumAddress, EventData, EventKey, Fee, L1ToL2MessageNonce,
@@ -194,10 +409,11 @@ pub mod transaction {
         CallParamAsDecimalStr, ConstructorParamAsDecimalStr, EthereumAddressAsHexStr,
         EventDataAsDecimalStr, EventKeyAsDecimalStr, L1ToL2MessagePayloadElemAsDecimalStr,
         L2ToL1MessagePayloadElemAsDecimalStr, TransactionSignatureElemAsDecimalStr,
-        TransactionVersionAsHexStr,
+        TransactionVersionAsFeltHexStr,
     };
     use serde::{Deserialize, Serialize};
     use serde_with::serde_as;
+    use std::collections::HashMap;
 
     /// Represents deserialized L2 transaction entry point values.
     #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -209,6 +425,15 @@ pub mod transaction {
         L1Handler,
     }
 
+    impl std::fmt::Display for EntryPointType {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.pad(match self {
+                EntryPointType::External => "EXTERNAL",
+                EntryPointType::L1Handler => "L1_HANDLER",
+            })
+        }
+    }
+
     /// Represents execution resources for L2 transaction.
     #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
@@ -218,6 +443,33 @@ pub mod transaction {
         pub n_memory_holes: u64,
     }
 
+    impl ExecutionResources {
+        /// Approximate number of bytes of VM memory used, i.e. every step and
+        /// memory hole accounts for one 32 byte memory cell.
+        ///
+        /// Saturates at [u64::MAX] for absurd counts.
+        pub fn memory_usage_bytes(&self) -> u64 {
+            self.n_steps
+                .saturating_add(self.n_memory_holes)
+                .saturating_mul(32)
+        }
+
+        /// L1 gas used by the computation given the `weights`.
+        ///
+        /// As per the Starknet fee model this is the cost of the most expensive resource,
+        /// rather than the sum of all of them.
+        pub fn gas_usage(&self, weights: &execution_resources::FeeWeights) -> u64 {
+            let steps = execution_resources::gas_cost(self.n_steps, weights.n_steps);
+            let builtins = self
+                .builtin_instance_counter
+                .normal()
+                .map(|counter| counter.max_cost(weights))
+                .unwrap_or_default();
+
+            steps.max(builtins)
+        }
+    }
+
     /// Types used when deserializing L2 execution resources related data.
     pub mod execution_resources {
         use serde::{Deserialize, Serialize};
@@ -231,19 +483,171 @@ pub mod transaction {
             Empty(EmptyBuiltinInstanceCounter),
         }
 
-        #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+        impl BuiltinInstanceCounter {
+            /// Returns the builtin counts, or [None] if the sequencer omitted them.
+            ///
+            /// Note that a counter with all counts zero is returned as is, i.e. it means
+            /// no builtins were used as opposed to the counts being unknown.
+            pub fn normal(&self) -> Option<&NormalBuiltinInstanceCounter> {
+                match self {
+                    BuiltinInstanceCounter::Normal(counter) => Some(counter),
+                    BuiltinInstanceCounter::Empty(_) => None,
+                }
+            }
+
+            /// Returns `true` if the sequencer omitted the builtin counts.
+            pub fn is_empty(&self) -> bool {
+                matches!(self, BuiltinInstanceCounter::Empty(_))
+            }
+        }
+
+        #[derive(Copy, Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
         #[serde(deny_unknown_fields)]
         pub struct NormalBuiltinInstanceCounter {
-            bitwise_builtin: u64,
-            ecdsa_builtin: u64,
-            ec_op_builtin: u64,
-            output_builtin: u64,
-            pedersen_builtin: u64,
-            range_check_builtin: u64,
+            pub bitwise_builtin: u64,
+            pub ecdsa_builtin: u64,
+            pub ec_op_builtin: u64,
+            pub output_builtin: u64,
+            pub pedersen_builtin: u64,
+            pub range_check_builtin: u64,
+        }
+
+        /// L1 gas cost per unit of each execution resource, in hundredths of a gas unit.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct FeeWeights {
+            pub n_steps: u64,
+            pub bitwise_builtin: u64,
+            pub ecdsa_builtin: u64,
+            pub ec_op_builtin: u64,
+            pub output_builtin: u64,
+            pub pedersen_builtin: u64,
+            pub range_check_builtin: u64,
+        }
+
+        /// The Starknet fee weights.
+        pub const FEE_WEIGHTS: FeeWeights = FeeWeights {
+            n_steps: 1,
+            bitwise_builtin: 64,
+            ecdsa_builtin: 2048,
+            ec_op_builtin: 1024,
+            output_builtin: 0,
+            pedersen_builtin: 32,
+            range_check_builtin: 16,
+        };
+
+        /// Gas cost of `count` resource units, rounded up to a whole gas unit.
+        ///
+        /// Saturates at [u64::MAX] for absurd counts.
+        pub(super) fn gas_cost(count: u64, weight: u64) -> u64 {
+            count.saturating_mul(weight).saturating_add(99) / 100
+        }
+
+        /// Gas costs of the individual builtins given the `weights`, e.g. [FEE_WEIGHTS].
+        impl NormalBuiltinInstanceCounter {
+            pub fn bitwise_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.bitwise_builtin, weights.bitwise_builtin)
+            }
+
+            pub fn ecdsa_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.ecdsa_builtin, weights.ecdsa_builtin)
+            }
+
+            pub fn ec_op_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.ec_op_builtin, weights.ec_op_builtin)
+            }
+
+            pub fn output_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.output_builtin, weights.output_builtin)
+            }
+
+            pub fn pedersen_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.pedersen_builtin, weights.pedersen_builtin)
+            }
+
+            pub fn range_check_cost(&self, weights: &FeeWeights) -> u64 {
+                gas_cost(self.range_check_builtin, weights.range_check_builtin)
+            }
+
+            /// Gas cost of the most expensive builtin given the `weights`.
+            pub fn max_cost(&self, weights: &FeeWeights) -> u64 {
+                [
+                    self.bitwise_cost(weights),
+                    self.ecdsa_cost(weights),
+                    self.ec_op_cost(weights),
+                    self.output_cost(weights),
+                    self.pedersen_cost(weights),
+                    self.range_check_cost(weights),
+                ]
+                .into_iter()
+                .max()
+                .unwrap_or_default()
+            }
         }
 
         #[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
         pub struct EmptyBuiltinInstanceCounter {}
+
+        #[cfg(test)]
+        mod tests {
+            use super::{BuiltinInstanceCounter, NormalBuiltinInstanceCounter, FEE_WEIGHTS};
+
+            #[test]
+            fn empty_and_zeroed_round_trip() {
+                let empty = serde_json::json!({});
+                let counter: BuiltinInstanceCounter =
+                    serde_json::from_value(empty.clone()).unwrap();
+                assert!(counter.is_empty());
+                assert_eq!(counter.normal(), None);
+                assert_eq!(serde_json::to_value(counter).unwrap(), empty);
+
+                let zeroed = serde_json::json!({
+                    "bitwise_builtin": 0,
+                    "ecdsa_builtin": 0,
+                    "ec_op_builtin": 0,
+                    "output_builtin": 0,
+                    "pedersen_builtin": 0,
+                    "range_check_builtin": 0,
+                });
+                let counter: BuiltinInstanceCounter =
+                    serde_json::from_value(zeroed.clone()).unwrap();
+                assert!(!counter.is_empty());
+                assert_eq!(counter.normal().unwrap().pedersen_builtin, 0);
+                assert_eq!(serde_json::to_value(counter).unwrap(), zeroed);
+            }
+
+            #[test]
+            fn builtin_costs() {
+                let counter = NormalBuiltinInstanceCounter {
+                    bitwise_builtin: 1,
+                    ecdsa_builtin: 2,
+                    ec_op_builtin: 3,
+                    output_builtin: 4,
+                    pedersen_builtin: 5,
+                    range_check_builtin: 100,
+                };
+
+                assert_eq!(counter.bitwise_cost(&FEE_WEIGHTS), 1);
+                assert_eq!(counter.ecdsa_cost(&FEE_WEIGHTS), 41);
+                assert_eq!(counter.ec_op_cost(&FEE_WEIGHTS), 31);
+                assert_eq!(counter.output_cost(&FEE_WEIGHTS), 0);
+                assert_eq!(counter.pedersen_cost(&FEE_WEIGHTS), 2);
+                assert_eq!(counter.range_check_cost(&FEE_WEIGHTS), 16);
+                assert_eq!(counter.max_cost(&FEE_WEIGHTS), 41);
+
+                let weights = super::FeeWeights {
+                    range_check_builtin: 100,
+                    ..FEE_WEIGHTS
+                };
+                assert_eq!(counter.range_check_cost(&weights), 100);
+                assert_eq!(counter.max_cost(&weights), 100);
+
+                let absurd = NormalBuiltinInstanceCounter {
+                    ecdsa_builtin: u64::MAX,
+                    ..counter
+                };
+                assert_eq!(absurd.ecdsa_cost(&FEE_WEIGHTS), u64::MAX / 100);
+            }
+        }
     }
 
     /// Represents deserialized L1 to L2 message.
@@ -261,6 +665,50 @@ pub mod transaction {
         pub nonce: Option<L1ToL2MessageNonce>,
     }
 
+    /// Well-known L1 handler selectors and their function names.
+    const KNOWN_SELECTORS: &[(EntryPoint, &str)] = &[
+        (
+            pathfinder_common::selector!("handle_deposit"),
+            "handle_deposit",
+        ),
+        (
+            pathfinder_common::selector!("handle_token_deposit"),
+            "handle_token_deposit",
+        ),
+    ];
+
+    impl L1ToL2Message {
+        /// Returns the function name of [selector](Self::selector) if it is a well-known selector.
+        pub fn selector_name(&self) -> Option<&'static str> {
+            KNOWN_SELECTORS
+                .iter()
+                .find(|(selector, _)| *selector == self.selector)
+                .map(|(_, name)| *name)
+        }
+
+        /// Computes the hash by which the Starknet core contract on L1 tracks this message, or
+        /// [None] if the message's nonce is unknown.
+        ///
+        /// This is the Keccak256 digest of the ABI packed encoding of `from_address`,
+        /// `to_address`, `nonce`, `selector`, the payload's length and the payload, where
+        /// every element is encoded as a big-endian `uint256`. The 20 byte `from_address` is
+        /// therefore left-padded with zeros.
+        pub fn message_hash(&self) -> Option<H256> {
+            let nonce = self.nonce?;
+
+            let header = [
+                ethereum_address_word(&self.from_address),
+                self.to_address.get().to_be_bytes(),
+                nonce.0.to_be_bytes(),
+                self.selector.0.to_be_bytes(),
+                length_word(self.payload.len()),
+            ];
+            let payload = self.payload.iter().map(|elem| elem.0.to_be_bytes());
+
+            Some(keccak_words(header.into_iter().chain(payload)))
+        }
+    }
+
     /// Represents deserialized L2 to L1 message.
     #[serde_as]
     #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -273,11 +721,55 @@ pub mod transaction {
         pub to_address: EthereumAddress,
     }
 
+    impl L2ToL1Message {
+        /// Computes the hash by which the Starknet core contract on L1 tracks this message.
+        ///
+        /// This is the Keccak256 digest of the ABI packed encoding of `from_address`,
+        /// `to_address`, the payload's length and the payload, where every element is encoded
+        /// as a big-endian `uint256`, see [L1ToL2Message::message_hash].
+        pub fn message_hash(&self) -> H256 {
+            let header = [
+                self.from_address.get().to_be_bytes(),
+                ethereum_address_word(&self.to_address),
+                length_word(self.payload.len()),
+            ];
+            let payload = self.payload.iter().map(|elem| elem.0.to_be_bytes());
+
+            keccak_words(header.into_iter().chain(payload))
+        }
+    }
+
+    /// Encodes the address as a `uint256`, i.e. left-padded with zeros.
+    fn ethereum_address_word(address: &EthereumAddress) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(address.0.as_bytes());
+        word
+    }
+
+    /// Encodes the length as a `uint256`.
+    fn length_word(len: usize) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(len as u64).to_be_bytes());
+        word
+    }
+
+    /// Computes the Keccak256 digest of the concatenated words.
+    fn keccak_words(words: impl IntoIterator<Item = [u8; 32]>) -> H256 {
+        use sha3::Digest;
+
+        let mut hasher = sha3::Keccak256::new();
+        for word in words {
+            hasher.update(word);
+        }
+        H256(hasher.finalize().into())
+    }
+
     /// Represents deserialized L2 transaction receipt data.
     #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
     #[serde(deny_unknown_fields)]
     pub struct Receipt {
-        #[serde(default)]
+        /// Some sequencer versions serve this as `l2_actual_fee`.
+        #[serde(default, alias = "l2_actual_fee")]
         pub actual_fee: Option<Fee>,
         pub events: Vec<Event>,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -288,6 +780,42 @@ pub mod transaction {
         pub transaction_index: TransactionIndex,
     }
 
+    impl Receipt {
+        /// Estimates the fee of the transaction's computation using the Starknet fee weights,
+        /// see [Receipt::estimated_fee_with].
+        pub fn estimated_fee(&self, gas_price: U256) -> U256 {
+            self.estimated_fee_with(gas_price, &execution_resources::FEE_WEIGHTS)
+        }
+
+        /// Estimates the fee of the transaction's computation as its [gas usage](ExecutionResources::gas_usage)
+        /// times `gas_price`.
+        ///
+        /// This does not include the cost of publishing the state diff to L1, so it is a lower
+        /// bound of the [actual fee](Receipt::actual_fee). Zero if the receipt has no execution
+        /// resources, and saturates at [U256::MAX].
+        pub fn estimated_fee_with(
+            &self,
+            gas_price: U256,
+            weights: &execution_resources::FeeWeights,
+        ) -> U256 {
+            let gas_usage = self
+                .execution_resources
+                .map(|resources| resources.gas_usage(weights))
+                .unwrap_or_default();
+
+            gas_price.saturating_mul(U256::from(gas_usage))
+        }
+
+        /// The receipt's events grouped by the contract which emitted them, in emission order.
+        pub fn events_by_contract(&self) -> HashMap<ContractAddress, Vec<&Event>> {
+            let mut events = HashMap::<_, Vec<_>>::new();
+            for event in &self.events {
+                events.entry(event.from_address).or_default().push(event);
+            }
+            events
+        }
+    }
+
     /// Represents deserialized L2 transaction event data.
     #[serde_as]
     #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -412,6 +940,55 @@ pub mod transaction {
                 Transaction::L1Handler(t) => t.contract_address,
             }
         }
+
+        /// Checks that the transaction's calldata, constructor calldata and signature do not
+        /// exceed [MAX_CALLDATA_SIZE] and [MAX_SIGNATURE_SIZE] elements respectively.
+        pub fn validate(&self) -> Result<(), TransactionValidationError> {
+            let (calldata, signature) = match self {
+                Transaction::Declare(t) => (0, t.signature().len()),
+                Transaction::Deploy(t) => (t.constructor_calldata.len(), 0),
+                Transaction::DeployAccount(t) => (t.constructor_calldata.len(), t.signature.len()),
+                Transaction::Invoke(InvokeTransaction::V0(t)) => {
+                    (t.calldata.len(), t.signature.len())
+                }
+                Transaction::Invoke(InvokeTransaction::V1(t)) => {
+                    (t.calldata.len(), t.signature.len())
+                }
+                Transaction::L1Handler(t) => (t.calldata.len(), 0),
+            };
+
+            if calldata > MAX_CALLDATA_SIZE {
+                return Err(TransactionValidationError::CalldataTooLarge {
+                    got: calldata,
+                    max: MAX_CALLDATA_SIZE,
+                });
+            }
+            if signature > MAX_SIGNATURE_SIZE {
+                return Err(TransactionValidationError::SignatureTooLarge {
+                    got: signature,
+                    max: MAX_SIGNATURE_SIZE,
+                });
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Maximum number of elements in a transaction's calldata or constructor calldata, which
+    /// is the sequencer's default limit.
+    pub const MAX_CALLDATA_SIZE: usize = 4000;
+
+    /// Maximum number of elements in a transaction's signature, which is the sequencer's
+    /// default limit.
+    pub const MAX_SIGNATURE_SIZE: usize = 4000;
+
+    /// Reasons for a [Transaction] to fail [validation](Transaction::validate).
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+    pub enum TransactionValidationError {
+        #[error("Calldata has {got} elements, at most {max} are allowed")]
+        CalldataTooLarge { got: usize, max: usize },
+        #[error("Signature has {got} elements, at most {max} are allowed")]
+        SignatureTooLarge { got: usize, max: usize },
     }
 
     #[derive(Clone, Debug, Serialize, PartialEq, Eq)]
@@ -436,7 +1013,7 @@ pub mod transaction {
             #[serde_as]
             #[derive(Deserialize)]
             struct Version {
-                #[serde_as(as = "TransactionVersionAsHexStr")]
+                #[serde_as(as = "TransactionVersionAsFeltHexStr")]
                 #[serde(default = "transaction_version_zero")]
                 pub version: TransactionVersion,
             }
@@ -518,7 +1095,7 @@ pub mod transaction {
         #[serde_as(as = "Vec<ConstructorParamAsDecimalStr>")]
         pub constructor_calldata: Vec<ConstructorParam>,
         pub transaction_hash: TransactionHash,
-        #[serde_as(as = "TransactionVersionAsHexStr")]
+        #[serde_as(as = "TransactionVersionAsFeltHexStr")]
         #[serde(default = "transaction_version_zero")]
         pub version: TransactionVersion,
     }
@@ -531,7 +1108,7 @@ pub mod transaction {
         pub contract_address: ContractAddress,
         pub transaction_hash: TransactionHash,
         pub max_fee: Fee,
-        #[serde_as(as = "TransactionVersionAsHexStr")]
+        #[serde_as(as = "TransactionVersionAsFeltHexStr")]
         pub version: TransactionVersion,
         #[serde_as(as = "Vec<TransactionSignatureElemAsDecimalStr>")]
         pub signature: Vec<TransactionSignatureElem>,
@@ -562,7 +1139,7 @@ pub mod transaction {
             #[serde_as]
             #[derive(Deserialize)]
             struct Version {
-                #[serde_as(as = "TransactionVersionAsHexStr")]
+                #[serde_as(as = "TransactionVersionAsFeltHexStr")]
                 #[serde(default = "transaction_version_zero")]
                 pub version: TransactionVersion,
             }
@@ -648,7 +1225,7 @@ pub mod transaction {
         pub nonce: TransactionNonce,
         pub calldata: Vec<CallParam>,
         pub transaction_hash: TransactionHash,
-        #[serde_as(as = "TransactionVersionAsHexStr")]
+        #[serde_as(as = "TransactionVersionAsFeltHexStr")]
         pub version: TransactionVersion,
     }
 
@@ -710,11 +1287,21 @@ pub enum MaybePendingStateUpdate {
     Pending(PendingStateUpdate),
 }
 
+impl MaybePendingStateUpdate {
+    /// Returns `true` if this is the state update of the pending block, which has no block hash.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, MaybePendingStateUpdate::Pending(_))
+    }
+}
+
 /// Used to deserialize replies to StarkNet state update requests except for the pending one.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct StateUpdate {
     pub block_hash: BlockHash,
+    /// Only returned by some sequencer endpoints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<BlockNumber>,
     pub new_root: StateCommitment,
     pub old_root: StateCommitment,
     pub state_diff: state_update::StateDiff,
@@ -842,6 +1429,119 @@ pub mod state_update {
     }
 }
 
+/// Used to deserialize replies to Starknet transaction trace requests.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionTrace {
+    /// The account's `__validate__` call, absent for transactions without one.
+    #[serde(default)]
+    pub validate_invocation: Option<trace::FunctionInvocation>,
+    /// Absent for declare transactions.
+    #[serde(default)]
+    pub function_invocation: Option<trace::FunctionInvocation>,
+    #[serde(default)]
+    pub fee_transfer_invocation: Option<trace::FunctionInvocation>,
+    pub signature: Vec<TransactionSignatureElem>,
+}
+
+/// Types used when deserializing transaction trace related data.
+pub mod trace {
+    use crate::request::contract::EntryPointType;
+    use pathfinder_common::{
+        CallParam, ClassHash, ContractAddress, EntryPoint, EthereumAddress, EventData, EventKey,
+        L2ToL1MessagePayloadElem,
+    };
+    use pathfinder_serde::EthereumAddressAsHexStr;
+    use serde::Deserialize;
+    use serde_with::serde_as;
+    use stark_hash::Felt;
+    use std::collections::HashMap;
+
+    /// A call made while executing a transaction, including the calls it made in turn.
+    ///
+    /// Older traces lack some of the fields.
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub struct FunctionInvocation {
+        #[serde(default)]
+        pub call_type: Option<CallType>,
+        #[serde(default)]
+        pub caller_address: Option<Felt>,
+        pub contract_address: ContractAddress,
+        #[serde(default)]
+        pub class_hash: Option<ClassHash>,
+        #[serde(default)]
+        pub entry_point_type: Option<EntryPointType>,
+        #[serde(default)]
+        pub selector: Option<EntryPoint>,
+        pub calldata: Vec<CallParam>,
+        #[serde(default)]
+        pub result: Vec<Felt>,
+        #[serde(default)]
+        pub execution_resources: Option<ExecutionResources>,
+        #[serde(default)]
+        pub internal_calls: Vec<FunctionInvocation>,
+        #[serde(default)]
+        pub events: Vec<Event>,
+        #[serde(default)]
+        pub messages: Vec<MessageToL1>,
+    }
+
+    impl FunctionInvocation {
+        /// Iterates over this invocation and all the calls made by it, depth-first in call order.
+        pub fn iter(&self) -> impl Iterator<Item = &FunctionInvocation> {
+            let mut stack = vec![self];
+            std::iter::from_fn(move || {
+                let invocation = stack.pop()?;
+                stack.extend(invocation.internal_calls.iter().rev());
+                Some(invocation)
+            })
+        }
+    }
+
+    /// Resources used by a [FunctionInvocation], including the calls it made.
+    ///
+    /// Unlike in a receipt, only the builtins which were actually used are counted.
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub struct ExecutionResources {
+        pub n_steps: u64,
+        pub n_memory_holes: u64,
+        pub builtin_instance_counter: HashMap<String, u64>,
+    }
+
+    #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub enum CallType {
+        #[serde(rename = "CALL")]
+        Call,
+        #[serde(rename = "DELEGATE")]
+        Delegate,
+    }
+
+    /// An event emitted by a [FunctionInvocation].
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub struct Event {
+        /// Position of the event among all events emitted by the transaction.
+        pub order: u64,
+        pub keys: Vec<EventKey>,
+        pub data: Vec<EventData>,
+    }
+
+    /// An L2 to L1 message sent by a [FunctionInvocation].
+    #[serde_as]
+    #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+    #[serde(deny_unknown_fields)]
+    pub struct MessageToL1 {
+        /// Position of the message among all messages sent by the transaction.
+        pub order: u64,
+        #[serde_as(as = "EthereumAddressAsHexStr")]
+        pub to_address: EthereumAddress,
+        pub payload: Vec<L2ToL1MessagePayloadElem>,
+    }
+}
+
 /// Used to deserialize replies to Starknet Ethereum contract requests.
 #[serde_as]
 #[derive(Clone, Debug, Deserialize)]
@@ -855,6 +1555,20 @@ pub struct EthContractAddresses {
     pub gps_statement_verifier: EthereumAddress,
 }
 
+/// Used to deserialize replies to Starknet fee estimation requests.
+///
+/// Failed estimations are reported by the sequencer as Starknet errors instead.
+#[serde_as]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub struct FeeEstimate {
+    #[serde_as(as = "U256AsBigDecimal")]
+    pub overall_fee: U256,
+    #[serde_as(as = "U256AsBigDecimal")]
+    pub gas_price: U256,
+    #[serde_as(as = "U256AsBigDecimal")]
+    pub gas_usage: U256,
+}
+
 pub mod add_transaction {
     use pathfinder_common::{ClassHash, ContractAddress, TransactionHash};
 
@@ -973,6 +1687,41 @@ mod tests {
             serde_json::from_str::<Transaction>(v0_8_2::transaction::INVOKE).unwrap();
         }
 
+        #[test]
+        fn state_update_is_pending() {
+            let state_update =
+                serde_json::from_str::<MaybePendingStateUpdate>(v0_11_0::state_update::PENDING)
+                    .unwrap();
+            assert!(state_update.is_pending());
+
+            let state_update = serde_json::from_str::<MaybePendingStateUpdate>(
+                v0_11_0::state_update::NUMBER_315700,
+            )
+            .unwrap();
+            assert!(!state_update.is_pending());
+        }
+
+        #[test]
+        fn state_update_with_block_number() {
+            use super::super::StateUpdate;
+            use pathfinder_common::BlockNumber;
+
+            let mut json =
+                serde_json::from_str::<serde_json::Value>(integration::state_update::NUMBER_283364)
+                    .unwrap();
+            let state_update = serde_json::from_value::<StateUpdate>(json.clone()).unwrap();
+            assert_eq!(state_update.block_number, None);
+
+            json.as_object_mut()
+                .unwrap()
+                .insert("block_number".to_owned(), serde_json::json!(283364));
+            let state_update = serde_json::from_value::<StateUpdate>(json).unwrap();
+            assert_eq!(
+                state_update.block_number,
+                Some(BlockNumber::new_or_panic(283364))
+            );
+        }
+
         #[test]
         fn legacy_l1_handler_is_invoke() {
             // In the times before L1 Handler became an official tx variant,
@@ -1002,4 +1751,690 @@ mod tests {
             assert_matches::assert_matches!(tx, TransactionVariant::L1Handler(_));
         }
     }
+
+    #[test]
+    fn transaction_validation() {
+        use super::transaction::{
+            InvokeTransaction, Transaction, TransactionValidationError, MAX_CALLDATA_SIZE,
+            MAX_SIGNATURE_SIZE,
+        };
+        use pathfinder_common::{CallParam, ConstructorParam, TransactionSignatureElem};
+        use stark_hash::Felt;
+        use starknet_gateway_test_fixtures::v0_11_0;
+
+        let deploy = serde_json::from_str::<super::Transaction>(
+            v0_11_0::transaction::deploy::v1::BLOCK_485004,
+        )
+        .unwrap()
+        .transaction
+        .unwrap();
+        let invoke = serde_json::from_str::<super::Transaction>(
+            v0_11_0::transaction::invoke::v1::BLOCK_790K,
+        )
+        .unwrap()
+        .transaction
+        .unwrap();
+        assert_eq!(deploy.validate(), Ok(()));
+        assert_eq!(invoke.validate(), Ok(()));
+
+        let mut oversized = deploy;
+        match &mut oversized {
+            Transaction::Deploy(t) => {
+                t.constructor_calldata = vec![ConstructorParam(Felt::ZERO); MAX_CALLDATA_SIZE + 1]
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            oversized.validate(),
+            Err(TransactionValidationError::CalldataTooLarge {
+                got: MAX_CALLDATA_SIZE + 1,
+                max: MAX_CALLDATA_SIZE,
+            })
+        );
+
+        let mut at_limit = invoke.clone();
+        match &mut at_limit {
+            Transaction::Invoke(InvokeTransaction::V1(t)) => {
+                t.calldata = vec![CallParam(Felt::ZERO); MAX_CALLDATA_SIZE];
+                t.signature = vec![TransactionSignatureElem(Felt::ZERO); MAX_SIGNATURE_SIZE];
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(at_limit.validate(), Ok(()));
+
+        let mut oversized = invoke;
+        match &mut oversized {
+            Transaction::Invoke(InvokeTransaction::V1(t)) => {
+                t.signature = vec![TransactionSignatureElem(Felt::ZERO); MAX_SIGNATURE_SIZE + 1]
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            oversized.validate(),
+            Err(TransactionValidationError::SignatureTooLarge {
+                got: MAX_SIGNATURE_SIZE + 1,
+                max: MAX_SIGNATURE_SIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn l1_handler_transaction_round_trip() {
+        use super::transaction::Transaction as TransactionVariant;
+        use super::Transaction;
+        use pathfinder_common::{felt, TransactionNonce};
+        use starknet_gateway_test_fixtures::v0_11_0;
+
+        let fixture = v0_11_0::transaction::l1_handler::v0::BLOCK_790K;
+        let reply = serde_json::from_str::<Transaction>(fixture).unwrap();
+
+        let tx = reply.transaction.unwrap();
+        match &tx {
+            TransactionVariant::L1Handler(l1_handler) => {
+                assert_eq!(l1_handler.nonce, TransactionNonce(felt!("0xa9b5d")))
+            }
+            other => panic!("Expected an L1 handler transaction, got {other:?}"),
+        }
+
+        let expected = serde_json::from_str::<serde_json::Value>(fixture).unwrap();
+        assert_eq!(serde_json::to_value(&tx).unwrap(), expected["transaction"]);
+    }
+
+    #[test]
+    fn receipt_l2_actual_fee_alias() {
+        use super::transaction::Receipt;
+        use pathfinder_common::{felt, Fee};
+
+        let json = serde_json::json!({
+            "l2_actual_fee": "0x1234",
+            "events": [],
+            "execution_resources": null,
+            "l1_to_l2_consumed_message": null,
+            "l2_to_l1_messages": [],
+            "transaction_hash": "0x1",
+            "transaction_index": 0
+        });
+
+        let receipt: Receipt = serde_json::from_value(json).unwrap();
+        assert_eq!(receipt.actual_fee, Some(Fee(felt!("0x1234"))));
+    }
+
+    #[test]
+    fn l2_to_l1_message_hash() {
+        use super::transaction::L2ToL1Message;
+        use ethers::types::{H160, H256};
+        use pathfinder_common::{felt, ContractAddress, EthereumAddress, L2ToL1MessagePayloadElem};
+        use std::str::FromStr;
+
+        // Messages sent by transaction 0x340e1cb4711b64e4a390a3dad219044f6894d51f1a9e4790bc996bf2444324f
+        // in block 231579 and transaction 0x437d6870afa8dfe36422ef1aa079dd74b71778d7dd5f17159e738a75f10dfa2
+        // in block 156000 of the 0.9.0 fixtures.
+        //
+        // The expected hashes are not taken from the L1 `LogMessageToL1` events, as the fixtures don't
+        // include the L1 transactions. They were computed independently of this code as the keccak256
+        // of the ABI encoded words `from_address, to_address, payload.len(), payload...`, which is
+        // how `StarknetMessaging.consumeMessageFromL2` hashes the message.
+        let cases = [
+            (
+                "0x73314940630fd6dcda0d772d4c972c4e0a9946bef9dabf4ef84eda8ef542b82",
+                "0xc3511006C04EF1d78af4C8E0e74Ec18A6E64Ff9e",
+                vec![
+                    felt!("0x0"),
+                    felt!("0xb09ee2223f74998417445f8b551e3f370a08e22"),
+                    felt!("0x16345785d8a0000"),
+                    felt!("0x0"),
+                ],
+                "0xc97df6d3e011be606231d19515d1b53351580071f4604f22f146fe80b28076b7",
+            ),
+            (
+                "0x2d6683ef6dc9b629d3f0196cc740db317b3e9158cdf769b1cd769ce88b0b9b9",
+                "0xe0fc9aE6F2e6896e8E01BAE143D1d8edA9F03F40",
+                vec![felt!("0xc"), felt!("0x22")],
+                "0xbe53e4c062c4b457ff23cfc074a82968134aeaa1f7a8b66f16976d6b851238af",
+            ),
+        ];
+
+        for (from_address, to_address, payload, expected) in cases {
+            let message = L2ToL1Message {
+                from_address: ContractAddress::new_or_panic(
+                    stark_hash::Felt::from_hex_str(from_address).unwrap(),
+                ),
+                payload: payload.into_iter().map(L2ToL1MessagePayloadElem).collect(),
+                to_address: EthereumAddress(H160::from_str(to_address).unwrap()),
+            };
+            assert_eq!(message.message_hash(), H256::from_str(expected).unwrap());
+        }
+    }
+
+    #[test]
+    fn problems_errors() {
+        use super::call::Problems;
+
+        let problems = Problems {
+            calldata: [
+                (3, vec!["third".to_owned()]),
+                (1, vec!["first".to_owned(), "second".to_owned()]),
+                (5, vec![]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        assert_eq!(problems.first_error(), Some("first"));
+        assert_eq!(problems.all_errors(), vec!["first", "second", "third"]);
+
+        let problems = Problems {
+            calldata: Default::default(),
+        };
+        assert_eq!(problems.first_error(), None);
+        assert!(problems.all_errors().is_empty());
+    }
+
+    #[test]
+    fn problems_serde() {
+        use super::call::Problems;
+
+        let cases = [
+            serde_json::json!({ "calldata": {} }),
+            serde_json::json!({ "calldata": { "0": ["err1"] } }),
+            serde_json::json!({
+                "calldata": {
+                    "1": ["err1", "err2"],
+                    "4": ["err3", "err4", "err5"],
+                }
+            }),
+        ];
+
+        for json in cases {
+            let problems = serde_json::from_value::<Problems>(json.clone()).unwrap();
+            assert_eq!(
+                problems.calldata.len(),
+                json["calldata"].as_object().unwrap().len()
+            );
+            assert_eq!(serde_json::to_value(&problems).unwrap(), json);
+        }
+
+        let problems = serde_json::from_value::<Problems>(serde_json::json!({
+            "calldata": { "1": ["err1", "err2"] }
+        }))
+        .unwrap();
+        assert_eq!(
+            problems.calldata[&1],
+            vec!["err1".to_owned(), "err2".to_owned()]
+        );
+    }
+
+    #[test]
+    fn l1_to_l2_message_selector_name() {
+        use super::transaction::L1ToL2Message;
+        use ethers::types::H160;
+        use pathfinder_common::{felt, ContractAddress, EntryPoint, EthereumAddress};
+
+        let mut message = L1ToL2Message {
+            from_address: EthereumAddress(H160::zero()),
+            payload: Vec::new(),
+            // sn_keccak("handle_deposit")
+            selector: EntryPoint(felt!(
+                "0x2d757788a8d8d6f21d1cd40bce38a8222d70654214e96ff95d8086e684fbee5"
+            )),
+            to_address: ContractAddress::new_or_panic(felt!("0x1")),
+            nonce: None,
+        };
+        assert_eq!(message.selector_name(), Some("handle_deposit"));
+
+        message.selector = EntryPoint(felt!("0x1234"));
+        assert_eq!(message.selector_name(), None);
+    }
+
+    #[test]
+    fn l1_to_l2_message_hash() {
+        use super::transaction::L1ToL2Message;
+        use ethers::types::{H160, H256};
+        use pathfinder_common::{
+            felt, ContractAddress, EntryPoint, EthereumAddress, L1ToL2MessageNonce,
+            L1ToL2MessagePayloadElem,
+        };
+        use std::str::FromStr;
+
+        // The message consumed by transaction 0x6556e1575c24834bafec5e4dd9e77c0baf8c7359b13458aad0033efa7c29034
+        // in block 231579 of the 0.9.0 fixtures.
+        //
+        // The expected hash is not taken from the L1 `LogMessageToL2` event, as the fixtures don't
+        // include the L1 transaction. It was computed independently of this code as the keccak256
+        // of the ABI encoded words `from_address, to_address, nonce, selector, payload.len(),
+        // payload...`, which is how `StarknetMessaging.getL1ToL2MsgHash` hashes the message.
+        let mut message = L1ToL2Message {
+            from_address: EthereumAddress(
+                H160::from_str("0xc3511006C04EF1d78af4C8E0e74Ec18A6E64Ff9e").unwrap(),
+            ),
+            payload: vec![
+                L1ToL2MessagePayloadElem(felt!(
+                    "0x8e1008a9324eb4142a020b602ffe0cf48f8a60db4787bc5114a411afa7524a"
+                )),
+                L1ToL2MessagePayloadElem(felt!("0x10a741a462780000")),
+                L1ToL2MessagePayloadElem(felt!("0x0")),
+            ],
+            selector: EntryPoint(felt!(
+                "0x2d757788a8d8d6f21d1cd40bce38a8222d70654214e96ff95d8086e684fbee5"
+            )),
+            to_address: ContractAddress::new_or_panic(felt!(
+                "0x73314940630fd6dcda0d772d4c972c4e0a9946bef9dabf4ef84eda8ef542b82"
+            )),
+            nonce: Some(L1ToL2MessageNonce(felt!("0x17e42"))),
+        };
+
+        let expected =
+            H256::from_str("0xfa0a91a1d448e9d2af199c5136c9e50aaf7e7f893aef42943ec5f6d678bb1894")
+                .unwrap();
+        assert_eq!(message.message_hash(), Some(expected));
+
+        message.nonce = None;
+        assert_eq!(message.message_hash(), None);
+    }
+
+    #[test]
+    fn status_predicates() {
+        use super::Status;
+
+        // (status, pending, accepted_on_l2, accepted_on_l1, finalized, failed)
+        let cases = [
+            (Status::NotReceived, false, false, false, false, false),
+            (Status::Received, false, false, false, false, false),
+            (Status::Pending, true, false, false, false, false),
+            (Status::Rejected, false, false, false, false, true),
+            (Status::AcceptedOnL1, false, false, true, true, false),
+            (Status::AcceptedOnL2, false, true, false, true, false),
+            (Status::Reverted, false, false, false, false, true),
+            (Status::Aborted, false, false, false, false, true),
+        ];
+
+        for (status, pending, accepted_on_l2, accepted_on_l1, finalized, failed) in cases {
+            assert_eq!(status.is_pending(), pending, "{status}");
+            assert_eq!(status.is_accepted_on_l2(), accepted_on_l2, "{status}");
+            assert_eq!(status.is_accepted_on_l1(), accepted_on_l1, "{status}");
+            assert_eq!(status.is_finalized(), finalized, "{status}");
+            assert_eq!(status.is_failed(), failed, "{status}");
+
+            let serialized = serde_json::to_value(status).unwrap();
+            assert_eq!(serialized, serde_json::json!(status.to_string()));
+            let name = serialized.as_str().unwrap();
+            assert_eq!(finalized, name.starts_with("ACCEPTED_ON_"), "{status}");
+            assert_eq!(accepted_on_l1, name.ends_with("_L1"), "{status}");
+            assert_eq!(accepted_on_l2, name.ends_with("_L2"), "{status}");
+        }
+    }
+
+    #[test]
+    fn entry_point_type_display() {
+        use super::transaction::EntryPointType;
+
+        for entry_point_type in [EntryPointType::External, EntryPointType::L1Handler] {
+            assert_eq!(
+                serde_json::to_value(entry_point_type).unwrap(),
+                serde_json::json!(entry_point_type.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn status_transitions() {
+        use super::Status;
+
+        let progress = [
+            Status::NotReceived,
+            Status::Received,
+            Status::Pending,
+            Status::AcceptedOnL2,
+            Status::AcceptedOnL1,
+        ];
+        let failures = [Status::Rejected, Status::Reverted, Status::Aborted];
+
+        for (i, status) in progress.iter().enumerate() {
+            for (j, other) in progress.iter().enumerate() {
+                assert_eq!(status.precedes(*other), i < j, "{status} -> {other}");
+            }
+
+            let terminal = *status == Status::AcceptedOnL1;
+            assert_eq!(status.is_terminal(), terminal, "{status}");
+            for failure in failures {
+                assert_eq!(status.precedes(failure), !terminal, "{status} -> {failure}");
+            }
+        }
+
+        for failure in failures {
+            assert!(failure.is_terminal(), "{failure}");
+            for other in progress.iter().chain(&failures) {
+                assert!(!failure.precedes(*other), "{failure} -> {other}");
+            }
+        }
+    }
+
+    #[test]
+    fn status_ordering() {
+        use super::Status;
+        use std::cmp::Ordering;
+
+        let all = [
+            Status::NotReceived,
+            Status::Received,
+            Status::Pending,
+            Status::Rejected,
+            Status::AcceptedOnL1,
+            Status::AcceptedOnL2,
+            Status::Reverted,
+            Status::Aborted,
+        ];
+
+        assert!(Status::NotReceived < Status::Received);
+        assert!(Status::Received < Status::Pending);
+        assert!(Status::Pending < Status::AcceptedOnL2);
+        assert!(Status::AcceptedOnL2 < Status::AcceptedOnL1);
+        assert_eq!(Status::Rejected.partial_cmp(&Status::Pending), None);
+        assert_eq!(Status::Rejected.partial_cmp(&Status::Reverted), None);
+
+        // There are few enough statuses to check the order's properties exhaustively.
+        for a in all {
+            assert_eq!(a.partial_cmp(&a), Some(Ordering::Equal), "{a}");
+
+            for b in all {
+                // Consistent with `PartialEq`, and antisymmetric.
+                assert_eq!(
+                    a.partial_cmp(&b) == Some(Ordering::Equal),
+                    a == b,
+                    "{a} {b}"
+                );
+                assert_eq!(
+                    a.partial_cmp(&b),
+                    b.partial_cmp(&a).map(Ordering::reverse),
+                    "{a} {b}"
+                );
+
+                for c in all {
+                    if a < b && b < c {
+                        assert!(a < c, "{a} {b} {c}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn execution_resources_memory_usage() {
+        use super::transaction::{execution_resources, ExecutionResources};
+
+        let resources = |n_steps, n_memory_holes| ExecutionResources {
+            builtin_instance_counter: execution_resources::BuiltinInstanceCounter::Empty(
+                execution_resources::EmptyBuiltinInstanceCounter {},
+            ),
+            n_steps,
+            n_memory_holes,
+        };
+
+        assert_eq!(resources(100, 28).memory_usage_bytes(), 4096);
+        assert_eq!(resources(u64::MAX / 32, 1).memory_usage_bytes(), u64::MAX);
+        assert_eq!(resources(u64::MAX, u64::MAX).memory_usage_bytes(), u64::MAX);
+    }
+
+    #[test]
+    fn receipt_estimated_fee() {
+        use super::Block;
+        use ethers::types::U256;
+        use pathfinder_common::Fee;
+        use stark_hash::Felt;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_231579).unwrap();
+        let gas_price = U256::from(block.gas_price.unwrap().0);
+
+        assert_eq!(gas_price, U256::from(1_500_000_008u64));
+
+        // 6059 steps are the most expensive resource, at 0.01 gas each.
+        let receipt = &block.transaction_receipts[0];
+        assert_eq!(
+            receipt.estimated_fee(gas_price),
+            U256::from(91_500_000_488u64)
+        );
+
+        // 10559 range checks at 0.16 gas each outweigh the 86435 steps. The block's gas price
+        // is zero, so any will do.
+        let other = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_90000).unwrap();
+        let receipt = &other.transaction_receipts[1];
+        assert_eq!(
+            receipt.estimated_fee(U256::from(1_000_000_000u64)),
+            U256::from(1_690_000_000_000u64)
+        );
+
+        // A declare without any execution.
+        let receipt = &block.transaction_receipts[30];
+        assert_eq!(receipt.estimated_fee(gas_price), U256::zero());
+
+        assert_eq!(
+            block.transaction_receipts[0].estimated_fee(U256::MAX),
+            U256::MAX
+        );
+
+        // Transactions which paid no fee, such as L1 handlers, can't be compared.
+        let paid = block
+            .transaction_receipts
+            .iter()
+            .filter(|receipt| receipt.actual_fee != Some(Fee(Felt::ZERO)));
+
+        for receipt in paid {
+            let actual_fee = U256::from_big_endian(receipt.actual_fee.unwrap().0.as_be_bytes());
+            assert!(receipt.estimated_fee(gas_price) <= actual_fee);
+        }
+    }
+
+    #[test]
+    fn receipt_events_by_contract() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_231579).unwrap();
+
+        for receipt in &block.transaction_receipts {
+            let events = receipt.events_by_contract();
+
+            for (address, contract_events) in &events {
+                let expected = receipt
+                    .events
+                    .iter()
+                    .filter(|event| event.from_address == *address)
+                    .collect::<Vec<_>>();
+                assert_eq!(contract_events, &expected);
+            }
+
+            let count = events.values().map(Vec::len).sum::<usize>();
+            assert_eq!(count, receipt.events.len());
+        }
+    }
+
+    #[test]
+    fn pending_block() {
+        use super::MaybePendingBlock;
+        use starknet_gateway_test_fixtures::{integration, v0_9_0};
+
+        for fixture in [v0_9_0::block::PENDING, integration::block::PENDING] {
+            let block = serde_json::from_str::<MaybePendingBlock>(fixture).unwrap();
+            assert!(block.is_pending());
+
+            match block {
+                MaybePendingBlock::Pending(pending) => {
+                    assert_ne!(pending.sequencer_address.0, stark_hash::Felt::ZERO)
+                }
+                MaybePendingBlock::Block(_) => panic!("Expected a pending block"),
+            }
+        }
+
+        let block =
+            serde_json::from_str::<MaybePendingBlock>(v0_9_0::block::NUMBER_156000).unwrap();
+        assert!(!block.is_pending());
+    }
+
+    #[test]
+    fn block_status_predicates() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_156000).unwrap();
+        assert_eq!(block.is_pending(), block.status.is_pending());
+        assert_eq!(block.is_accepted_on_l2(), block.status.is_accepted_on_l2());
+        assert_eq!(block.is_accepted_on_l1(), block.status.is_accepted_on_l1());
+        assert_eq!(block.is_finalized(), block.status.is_finalized());
+        assert_eq!(block.is_failed(), block.status.is_failed());
+        assert!(block.is_finalized());
+    }
+
+    #[test]
+    fn transaction_trace() {
+        use super::trace::CallType;
+        use super::TransactionTrace;
+        use pathfinder_common::{felt, ContractAddress, EntryPoint};
+        use starknet_gateway_test_fixtures::v0_11_0;
+
+        let trace = serde_json::from_str::<TransactionTrace>(v0_11_0::transaction_trace::INVOKE_V1)
+            .unwrap();
+        assert_eq!(trace.signature.len(), 4);
+        assert_eq!(trace.validate_invocation, None);
+        assert_eq!(trace.fee_transfer_invocation, None);
+
+        // __execute__ delegates to the account's implementation, which calls the ERC20 contract,
+        // which in turn delegates to its implementation.
+        let invocation = trace.function_invocation.unwrap();
+        let calls = invocation.iter().collect::<Vec<_>>();
+        let call_types = calls.iter().map(|call| call.call_type).collect::<Vec<_>>();
+        assert_eq!(
+            call_types,
+            [
+                Some(CallType::Call),
+                Some(CallType::Delegate),
+                Some(CallType::Call),
+                Some(CallType::Delegate)
+            ]
+        );
+
+        let transfer = calls[2];
+        assert_eq!(
+            transfer.contract_address,
+            ContractAddress::new_or_panic(felt!(
+                "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"
+            ))
+        );
+        assert_eq!(
+            transfer.caller_address,
+            Some(*invocation.contract_address.get())
+        );
+        assert_eq!(transfer.selector, Some(EntryPoint::hashed(b"transfer")));
+        assert_eq!(transfer.result, [felt!("0x1")]);
+
+        // The ERC20 Transfer event is emitted first, although by the innermost call.
+        assert_eq!(calls[3].events[0].order, 0);
+        assert_eq!(calls[1].events[0].order, 1);
+
+        let resources = invocation.execution_resources.as_ref().unwrap();
+        assert_eq!(resources.n_steps, 865);
+        assert_eq!(resources.n_memory_holes, 45);
+        assert_eq!(
+            resources.builtin_instance_counter,
+            [
+                ("pedersen_builtin".to_owned(), 4),
+                ("range_check_builtin".to_owned(), 24)
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn block_stats() {
+        use super::{Block, BlockStats};
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_156000).unwrap();
+        let stats = BlockStats::from(&block);
+
+        assert_eq!(stats.transaction_count, 29);
+        assert_eq!(stats.event_count, 55);
+        assert_eq!(stats.n_steps, 65357);
+        assert_eq!(stats.builtins.pedersen_builtin, 881);
+        assert_eq!(stats.builtins.range_check_builtin, 2681);
+    }
+
+    #[test]
+    fn block_stats_saturate() {
+        use super::{Block, BlockStats};
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let mut block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_156000).unwrap();
+        let resources = block.transaction_receipts[0]
+            .execution_resources
+            .as_mut()
+            .unwrap();
+        resources.n_steps = u64::MAX;
+        match &mut resources.builtin_instance_counter {
+            super::transaction::execution_resources::BuiltinInstanceCounter::Normal(counter) => {
+                counter.pedersen_builtin = u64::MAX
+            }
+            other => panic!("Expected builtin counts, got {other:?}"),
+        }
+
+        let stats = BlockStats::from(&block);
+        assert_eq!(stats.n_steps, u64::MAX);
+        assert_eq!(stats.builtins.pedersen_builtin, u64::MAX);
+        assert_eq!(stats.builtins.range_check_builtin, 2681);
+    }
+
+    #[test]
+    fn block_transactions_absent() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::TRANSACTIONS_ABSENT).unwrap();
+        assert!(block.transactions.is_empty());
+        assert!(block.transaction_receipts.is_empty());
+        block.validate_receipts_match_transactions().unwrap();
+    }
+
+    #[test]
+    fn block_receipts_mismatch() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let mut block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_156000).unwrap();
+        block.validate_receipts_match_transactions().unwrap();
+
+        block.transaction_receipts.clear();
+        block.validate_receipts_match_transactions().unwrap_err();
+    }
+
+    #[test]
+    fn block_event_count() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_156000).unwrap();
+        assert_eq!(block.event_count(), 55);
+    }
+
+    #[test]
+    fn block_l2_to_l1_message_count() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_1716).unwrap();
+        assert_eq!(block.l2_to_l1_message_count(), 3);
+    }
+
+    #[test]
+    fn block_transactions_by_hash() {
+        use super::Block;
+        use starknet_gateway_test_fixtures::v0_9_0;
+
+        let block = serde_json::from_str::<Block>(v0_9_0::block::NUMBER_1716).unwrap();
+        let by_hash = block.transactions_by_hash();
+
+        assert_eq!(by_hash.len(), block.transactions.len());
+        for tx in &block.transactions {
+            assert_eq!(by_hash[&tx.hash()], tx);
+        }
+    }
 }
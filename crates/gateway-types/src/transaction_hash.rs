@@ -10,9 +10,7 @@ use pathfinder_common::{
     TransactionNonce, TransactionVersion,
 };
 
-use crate::class_hash::truncated_keccak;
 use pathfinder_common::ChainId;
-use sha3::{Digest, Keccak256};
 use stark_hash::{Felt, HashChain};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -169,12 +167,7 @@ fn compute_declare_v2_hash(txn: &DeclareTransactionV2, chain_id: ChainId) -> Tra
 ///
 /// Where `h` is [Pedersen hash](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#pedersen_hash), and `sn_keccak` is [Starknet Keccak](https://docs.starknet.io/documentation/architecture_and_concepts/Hashing/hash-functions/#Starknet-keccak)
 fn compute_deploy_hash(txn: &DeployTransaction, chain_id: ChainId) -> TransactionHash {
-    lazy_static::lazy_static!(
-        static ref CONSTRUCTOR: EntryPoint = {
-            let mut keccak = Keccak256::default();
-            keccak.update(b"constructor");
-            EntryPoint(truncated_keccak(<[u8; 32]>::from(keccak.finalize())))};
-    );
+    const CONSTRUCTOR: EntryPoint = pathfinder_common::selector!("constructor");
 
     let constructor_params_hash = {
         let hh = txn.constructor_calldata.iter().fold(
@@ -191,7 +184,7 @@ fn compute_deploy_hash(txn: &DeployTransaction, chain_id: ChainId) -> Transactio
         b"deploy",
         txn.version,
         txn.contract_address,
-        Some(*CONSTRUCTOR),
+        Some(CONSTRUCTOR),
         constructor_params_hash,
         None,
         chain_id,
@@ -205,7 +198,7 @@ fn compute_deploy_hash(txn: &DeployTransaction, chain_id: ChainId) -> Transactio
         legacy_compute_txn_hash(
             b"deploy",
             txn.contract_address,
-            Some(*CONSTRUCTOR),
+            Some(CONSTRUCTOR),
             constructor_params_hash,
             chain_id,
         )
@@ -34,12 +34,53 @@ impl<'tx> ClassCommitmentTree<'tx> {
         self.tree.set(&self.storage, class.view_bits(), value.0)
     }
 
+    /// Returns the leaf hash stored for the given class, if any.
+    pub fn get(&self, class: SierraHash) -> anyhow::Result<Option<ClassCommitmentLeafHash>> {
+        let value = self.tree.get(&self.storage, class.view_bits())?;
+        Ok(value.map(ClassCommitmentLeafHash))
+    }
+
     /// Applies and persists any changes. Returns the new global root.
     pub fn commit_and_persist_changes(self) -> anyhow::Result<ClassCommitment> {
         let update = self.tree.commit()?;
-        for (hash, node) in update.added {
-            self.storage.insert(&hash, &node)?;
-        }
+        self.storage.insert_batch(&update.added)?;
         Ok(ClassCommitment(update.root))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pathfinder_common::felt;
+
+    #[test]
+    fn get() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE tree_class (hash BLOB PRIMARY KEY, data BLOB)",
+            [],
+        )
+        .unwrap();
+        let transaction = conn.transaction().unwrap();
+
+        let class = SierraHash(felt!("0x1"));
+        let leaf = ClassCommitmentLeafHash(felt!("0x2"));
+        let missing = SierraHash(felt!("0x3"));
+
+        let mut tree = ClassCommitmentTree::load(&transaction, ClassCommitment::ZERO);
+        assert_eq!(tree.get(class).unwrap(), None);
+        tree.set(class, leaf).unwrap();
+        tree.set(
+            SierraHash(felt!("0x4")),
+            ClassCommitmentLeafHash(felt!("0x5")),
+        )
+        .unwrap();
+        // Uncommitted changes are visible as well.
+        assert_eq!(tree.get(class).unwrap(), Some(leaf));
+        let root = tree.commit_and_persist_changes().unwrap();
+
+        let tree = ClassCommitmentTree::load(&transaction, root);
+        assert_eq!(tree.get(class).unwrap(), Some(leaf));
+        assert_eq!(tree.get(missing).unwrap(), None);
+    }
+}
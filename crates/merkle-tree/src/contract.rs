@@ -3,23 +3,69 @@
 //!
 //! These are abstractions built-on the [Binary Merkle-Patricia Tree](MerkleTree).
 
+use crate::range_proof::{Overlap, RangeProof};
+use crate::storage_proof::{ContractData, ContractStorageProof};
 use crate::PedersenHash;
 use crate::{
     merkle_node::InternalNode,
+    storage::Storage,
     tree::{MerkleTree, Visit},
 };
 use anyhow::Context;
-use bitvec::{prelude::Msb0, slice::BitSlice};
+use bitvec::{prelude::BitVec, prelude::Msb0, slice::BitSlice};
 use pathfinder_common::{
-    ContractAddress, ContractRoot, ContractStateHash, StorageAddress, StorageCommitment,
-    StorageValue,
+    BlockNumber, ClassHash, ContractAddress, ContractNonce, ContractRoot, ContractStateHash,
+    StorageAddress, StorageCommitment, StorageValue,
 };
+use pathfinder_storage::{ContractRootHistoryTable, ContractsStateTable, StarknetBlocksTable};
 use rusqlite::Transaction;
+use stark_hash::Felt;
 use std::ops::ControlFlow;
 
 crate::define_sqlite_storage!(ContractsStorage, "tree_contracts");
 crate::define_sqlite_storage!(GlobalStorage, "tree_global");
 
+impl<'tx> ContractsStorage<'tx> {
+    /// Returns all historical storage roots of the contract, ordered from oldest to newest.
+    ///
+    /// See [ContractRootHistoryTable].
+    pub fn list_contract_roots(
+        &self,
+        address: ContractAddress,
+    ) -> anyhow::Result<Vec<ContractRoot>> {
+        ContractRootHistoryTable::get_roots(self.0, address)
+    }
+}
+
+impl<'tx> GlobalStorage<'tx> {
+    /// Returns the distinct storage commitments of all stored blocks whose root node is
+    /// present in the tree, i.e. the historical roots which can still be loaded.
+    pub fn list_known_roots(&self) -> anyhow::Result<Vec<StorageCommitment>> {
+        let mut stmt = self
+            .0
+            .prepare(
+                "SELECT DISTINCT starknet_blocks.root FROM starknet_blocks
+                JOIN tree_global ON tree_global.hash = starknet_blocks.root",
+            )
+            .context("Preparing statement")?;
+
+        let roots = stmt
+            .query_map([], |row| row.get(0))
+            .context("Querying known roots")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Reading known roots")?;
+
+        Ok(roots)
+    }
+}
+
+/// Error returned by [ContractsStorageTree::load_checked] if the root node is not in storage.
+#[derive(Debug, thiserror::Error)]
+#[error("Contract root {root} not found in storage")]
+pub struct RootNotFound {
+    pub root: ContractRoot,
+}
+
 /// A [Patricia Merkle tree](MerkleTree) used to calculate commitments to a Starknet contract's storage.
 ///
 /// It maps a contract's [storage addresses](StorageAddress) to their [values](StorageValue).
@@ -38,27 +84,94 @@ impl<'tx> ContractsStorageTree<'tx> {
         Self { tree, storage }
     }
 
+    /// Returns all historical storage roots of the contract, see
+    /// [ContractsStorage::list_contract_roots].
+    pub fn list_contract_roots(
+        &self,
+        address: ContractAddress,
+    ) -> anyhow::Result<Vec<ContractRoot>> {
+        self.storage.list_contract_roots(address)
+    }
+
+    /// Loads the contract's storage tree as of the given block, or [None] if either the block
+    /// or the contract at that block is unknown.
+    pub fn at_block(
+        transaction: &'tx Transaction<'tx>,
+        address: ContractAddress,
+        block: BlockNumber,
+    ) -> anyhow::Result<Option<Self>> {
+        let storage_commitment_tree = match StorageCommitmentTree::at_block(transaction, block)? {
+            Some(tree) => tree,
+            None => return Ok(None),
+        };
+
+        let state_hash = match storage_commitment_tree
+            .get(address)
+            .context("Querying contract state hash")?
+        {
+            Some(state_hash) => state_hash,
+            None => return Ok(None),
+        };
+
+        let root = ContractsStateTable::get_root(transaction, state_hash)
+            .context("Querying contract root")?
+            .context("Contract state is missing")?;
+
+        Ok(Some(Self::load(transaction, root)))
+    }
+
+    /// Same as [load](Self::load), but fails with [RootNotFound] if the root node is
+    /// not present in storage. The root of an empty tree is always valid.
+    pub fn load_checked(
+        transaction: &'tx Transaction<'tx>,
+        root: ContractRoot,
+    ) -> anyhow::Result<Self> {
+        let tree = Self::load(transaction, root);
+
+        if root != ContractRoot::ZERO && tree.storage.get(&root.0)?.is_none() {
+            return Err(RootNotFound { root }.into());
+        }
+
+        Ok(tree)
+    }
+
     #[allow(dead_code)]
     pub fn get(&self, address: StorageAddress) -> anyhow::Result<Option<StorageValue>> {
         let value = self.tree.get(&self.storage, address.view_bits())?;
         Ok(value.map(StorageValue))
     }
 
-    /// Generates a proof for `key`. See [`MerkleTree::get_proof`].
-    pub fn get_proof(&self, key: &BitSlice<Msb0, u8>) -> anyhow::Result<Vec<crate::Node>> {
-        self.tree.get_proof(&self.storage, key)
+    /// Generates a proof for the given `address`. See [`MerkleTree::get_proof`].
+    pub fn get_proof(&self, address: &StorageAddress) -> anyhow::Result<Vec<crate::Node>> {
+        self.tree.get_proof(&self.storage, address.view_bits())
     }
 
+    /// Sets the storage value at `address`.
+    ///
+    /// Zero values are not stored: setting a value to zero removes the entry from the tree,
+    /// just like [delete](Self::delete).
     pub fn set(&mut self, address: StorageAddress, value: StorageValue) -> anyhow::Result<()> {
         self.tree.set(&self.storage, address.view_bits(), value.0)
     }
 
+    /// Removes the storage entry at `address`, the same as setting its value to zero.
+    ///
+    /// The resulting tree is identical to one in which `address` was never set.
+    /// See [`MerkleTree::delete`].
+    pub fn delete(&mut self, address: StorageAddress) -> anyhow::Result<()> {
+        self.tree.delete(&self.storage, address.view_bits())
+    }
+
+    /// Returns the root the tree would have if it were committed now, without persisting
+    /// anything. See [`MerkleTree::root_without_commit`].
+    pub fn root(&self) -> ContractRoot {
+        ContractRoot(self.tree.root_without_commit())
+    }
+
     /// Applies and persists any changes. Returns the new tree root.
     pub fn commit_and_persist_changes(self) -> anyhow::Result<ContractRoot> {
         let update = self.tree.commit()?;
-        for (hash, node) in update.added {
-            self.storage.insert(&hash, &node)?;
-        }
+        self.storage.insert_batch(&update.added)?;
         Ok(ContractRoot(update.root))
     }
 
@@ -69,6 +182,11 @@ impl<'tx> ContractsStorageTree<'tx> {
     ) -> anyhow::Result<Option<B>> {
         self.tree.dfs(&self.storage, f)
     }
+
+    /// See [`MerkleTree::leaves`]
+    pub fn leaves(&self) -> impl Iterator<Item = anyhow::Result<(BitVec<Msb0, u8>, Felt)>> + '_ {
+        self.tree.leaves(&self.storage)
+    }
 }
 
 /// A [Patricia Merkle tree](MerkleTree) used to calculate commitments to all of Starknet's storage.
@@ -89,6 +207,23 @@ impl<'tx> StorageCommitmentTree<'tx> {
         Self { tree, storage }
     }
 
+    /// Loads the tree at the storage commitment of the given block, or [None] if the block is unknown.
+    pub fn at_block(
+        transaction: &'tx Transaction<'tx>,
+        block: BlockNumber,
+    ) -> anyhow::Result<Option<Self>> {
+        let root = StarknetBlocksTable::get_storage_commitment(transaction, block.into())
+            .context("Querying storage commitment")?;
+
+        Ok(root.map(|root| Self::load(transaction, root)))
+    }
+
+    /// Returns the historical storage commitments which can still be loaded, see
+    /// [GlobalStorage::list_known_roots].
+    pub fn list_known_roots(&self) -> anyhow::Result<Vec<StorageCommitment>> {
+        self.storage.list_known_roots()
+    }
+
     pub fn get(&self, address: ContractAddress) -> anyhow::Result<Option<ContractStateHash>> {
         let value = self.tree.get(&self.storage, address.view_bits())?;
         Ok(value.map(ContractStateHash))
@@ -102,12 +237,33 @@ impl<'tx> StorageCommitmentTree<'tx> {
         self.tree.set(&self.storage, address.view_bits(), value.0)
     }
 
+    /// Sets the state hashes of many contracts in a single pass over the tree, see
+    /// [MerkleTree::set_batch].
+    ///
+    /// Equivalent to calling [StorageCommitmentTree::set] for each update in order, so the
+    /// last update of a contract wins, and a [ContractStateHash::ZERO] removes the contract.
+    pub fn batch_set(
+        &mut self,
+        updates: impl IntoIterator<Item = (ContractAddress, ContractStateHash)>,
+    ) -> anyhow::Result<()> {
+        let entries = updates
+            .into_iter()
+            .map(|(address, value)| (address.view_bits().to_bitvec(), value.0))
+            .collect::<Vec<_>>();
+
+        self.tree.set_batch(&self.storage, &entries)
+    }
+
+    /// Returns the global root the tree would have if it were committed now, without
+    /// persisting anything. See [`MerkleTree::root_without_commit`].
+    pub fn root(&self) -> StorageCommitment {
+        StorageCommitment(self.tree.root_without_commit())
+    }
+
     /// Applies and persists any changes. Returns the new global root.
     pub fn commit_and_persist_changes(self) -> anyhow::Result<StorageCommitment> {
         let update = self.tree.commit()?;
-        for (hash, node) in update.added {
-            self.storage.insert(&hash, &node)?;
-        }
+        self.storage.insert_batch(&update.added)?;
         Ok(StorageCommitment(update.root))
     }
 
@@ -123,4 +279,691 @@ impl<'tx> StorageCommitmentTree<'tx> {
     ) -> anyhow::Result<Option<B>> {
         self.tree.dfs(&self.storage, f)
     }
+
+    /// See [`MerkleTree::leaves`]
+    pub fn leaves(&self) -> impl Iterator<Item = anyhow::Result<(BitVec<Msb0, u8>, Felt)>> + '_ {
+        self.tree.leaves(&self.storage)
+    }
+
+    /// Generates a [RangeProof] of the state of all contracts with addresses in `start..=end`,
+    /// which can be checked using [verify_range_proof](crate::verify_range_proof).
+    ///
+    /// Only the subtrees which overlap the range are loaded from storage.
+    pub fn get_range_proof(
+        &self,
+        start: ContractAddress,
+        end: ContractAddress,
+    ) -> anyhow::Result<RangeProof> {
+        anyhow::ensure!(start <= end, "Range start is after its end");
+
+        let start_proof = self.get_proof(&start)?;
+        let end_proof = self.get_proof(&end)?;
+
+        let mut leaves = Vec::new();
+        self.dfs(&mut |node, path| {
+            match Overlap::of(path, start.view_bits(), end.view_bits()) {
+                Overlap::Outside => return ControlFlow::Continue(Visit::StopSubtree),
+                Overlap::Inside | Overlap::Partial => {}
+            }
+            if let InternalNode::Leaf(state_hash) = node {
+                leaves.push((path.to_bitvec(), ContractStateHash(*state_hash)));
+            }
+            ControlFlow::<(), _>::Continue(Visit::ContinueDeeper)
+        })?;
+
+        let leaves = leaves
+            .into_iter()
+            .map(|(key, state_hash)| {
+                let address = ContractAddress::from_bits(&key).context("Converting key")?;
+                Ok((address, state_hash))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(RangeProof {
+            start_proof,
+            end_proof,
+            leaves,
+        })
+    }
+
+    /// Returns the addresses of all contracts whose state differs between the two storage
+    /// commitments. See [`MerkleTree::diff_roots`].
+    pub fn diff_roots(
+        transaction: &'tx Transaction<'tx>,
+        root_a: StorageCommitment,
+        root_b: StorageCommitment,
+    ) -> anyhow::Result<Vec<ContractAddress>> {
+        let storage = GlobalStorage::new(transaction);
+        MerkleTree::<PedersenHash, 251>::diff_roots(&storage, root_a.0, root_b.0)?
+            .into_iter()
+            .map(|key| ContractAddress::from_bits(&key).context("Converting key"))
+            .collect()
+    }
+}
+
+/// Provides the contract state a Cairo VM execution reads from.
+///
+/// Contracts which are not deployed read as having zeroed storage, class hash and nonce.
+pub trait StateReader {
+    fn get_storage(
+        &self,
+        address: ContractAddress,
+        key: StorageAddress,
+    ) -> anyhow::Result<StorageValue>;
+
+    fn get_class_hash(&self, address: ContractAddress) -> anyhow::Result<ClassHash>;
+
+    fn get_nonce(&self, address: ContractAddress) -> anyhow::Result<ContractNonce>;
+}
+
+impl<'tx> StorageCommitmentTree<'tx> {
+    /// Returns the root, class hash and nonce of the contract's state, or [None] if the
+    /// contract is not deployed.
+    fn contract_state(
+        &self,
+        address: ContractAddress,
+    ) -> anyhow::Result<Option<(ContractRoot, ClassHash, ContractNonce)>> {
+        let state_hash = match self.get(address).context("Reading contract state hash")? {
+            Some(state_hash) => state_hash,
+            None => return Ok(None),
+        };
+
+        let state = ContractsStateTable::get_root_class_hash_and_nonce(self.storage.0, state_hash)
+            .context("Reading contract state")?
+            .with_context(|| format!("Contract state {} is missing", state_hash.0))?;
+
+        Ok(Some(state))
+    }
+
+    /// Generates a [ContractStorageProof] of the contract's storage slot `key`, which can be
+    /// checked using [verify_contract_storage_proof](crate::verify_contract_storage_proof).
+    pub fn get_contract_state_hash_proof(
+        &self,
+        address: ContractAddress,
+        key: StorageAddress,
+    ) -> anyhow::Result<ContractStorageProof> {
+        let contract_proof = self
+            .get_proof(&address)
+            .context("Generating contract proof")?;
+
+        let contract_data = match self.contract_state(address)? {
+            Some((root, class_hash, nonce)) => {
+                let storage_proof = ContractsStorageTree::load(self.storage.0, root)
+                    .get_proof(&key)
+                    .context("Generating storage proof")?;
+
+                Some(ContractData {
+                    class_hash,
+                    nonce,
+                    root,
+                    storage_proof,
+                })
+            }
+            None => None,
+        };
+
+        Ok(ContractStorageProof {
+            contract_proof,
+            contract_data,
+        })
+    }
+}
+
+impl StateReader for StorageCommitmentTree<'_> {
+    fn get_storage(
+        &self,
+        address: ContractAddress,
+        key: StorageAddress,
+    ) -> anyhow::Result<StorageValue> {
+        let root = match self.contract_state(address)? {
+            Some((root, _, _)) => root,
+            None => return Ok(StorageValue::ZERO),
+        };
+
+        let value = ContractsStorageTree::load(self.storage.0, root)
+            .get(key)
+            .context("Reading contract storage")?;
+
+        Ok(value.unwrap_or(StorageValue::ZERO))
+    }
+
+    fn get_class_hash(&self, address: ContractAddress) -> anyhow::Result<ClassHash> {
+        let state = self.contract_state(address)?;
+        Ok(state.map_or(ClassHash::ZERO, |(_, class_hash, _)| class_hash))
+    }
+
+    fn get_nonce(&self, address: ContractAddress) -> anyhow::Result<ContractNonce> {
+        let state = self.contract_state(address)?;
+        Ok(state.map_or(ContractNonce::ZERO, |(_, _, nonce)| nonce))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pathfinder_common::felt;
+
+    /// Opens an in-memory database with the tables backing the contract trees, and the
+    /// contract states, contract roots and block storage commitments they refer to.
+    pub(crate) fn open_database() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE tree_global (hash BLOB PRIMARY KEY, data BLOB);
+            CREATE TABLE tree_contracts (hash BLOB PRIMARY KEY, data BLOB);
+            CREATE TABLE contract_states (
+                state_hash BLOB PRIMARY KEY,
+                hash BLOB NOT NULL,
+                root BLOB NOT NULL,
+                nonce BLOB NOT NULL
+            );
+            CREATE TABLE contract_root_history (
+                block_number INTEGER,
+                contract_address BLOB NOT NULL,
+                root BLOB NOT NULL
+            );
+            CREATE TABLE starknet_blocks (number INTEGER PRIMARY KEY, root BLOB NOT NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    /// The `i`th of a sequence of keys spread out over the key space, so that they don't
+    /// all share a long common prefix.
+    pub(crate) fn spread_key(i: u64) -> Felt {
+        Felt::from_u64(i * 7919)
+    }
+
+    #[test]
+    fn list_known_roots() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let mut tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        tree.set(
+            ContractAddress::new_or_panic(felt!("0x1")),
+            ContractStateHash(felt!("0x2")),
+        )
+        .unwrap();
+        let root = tree.commit_and_persist_changes().unwrap();
+
+        // Two blocks sharing the same root, and one whose root node is not in the tree.
+        let unknown = StorageCommitment(felt!("0x1234"));
+        for (number, root) in [(0, root), (1, root), (2, unknown)] {
+            transaction
+                .execute(
+                    "INSERT INTO starknet_blocks (number, root) VALUES (?, ?)",
+                    rusqlite::params![number, root],
+                )
+                .unwrap();
+        }
+
+        let roots = StorageCommitmentTree::load(&transaction, root)
+            .list_known_roots()
+            .unwrap();
+        assert_eq!(roots, vec![root]);
+    }
+
+    #[test]
+    fn list_contract_roots() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let contract = ContractAddress::new_or_panic(felt!("0x1"));
+        let other = ContractAddress::new_or_panic(felt!("0x2"));
+        let roots = [ContractRoot(felt!("0x10")), ContractRoot(felt!("0x11"))];
+        // Inserted out of order, and interleaved with another contract's root.
+        ContractRootHistoryTable::insert(
+            &transaction,
+            BlockNumber::new_or_panic(3),
+            contract,
+            roots[1],
+        )
+        .unwrap();
+        ContractRootHistoryTable::insert(
+            &transaction,
+            BlockNumber::new_or_panic(2),
+            other,
+            roots[1],
+        )
+        .unwrap();
+        ContractRootHistoryTable::insert(
+            &transaction,
+            BlockNumber::new_or_panic(1),
+            contract,
+            roots[0],
+        )
+        .unwrap();
+
+        let tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        assert_eq!(tree.list_contract_roots(contract).unwrap(), roots);
+        assert_eq!(tree.list_contract_roots(other).unwrap(), vec![roots[1]]);
+        assert_eq!(
+            tree.list_contract_roots(ContractAddress::new_or_panic(felt!("0x3")))
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn storage_commitment_at_block() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let address = ContractAddress::new_or_panic(felt!("0x1"));
+        let state_hash = ContractStateHash(felt!("0x2"));
+        let mut tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        tree.set(address, state_hash).unwrap();
+        let root = tree.commit_and_persist_changes().unwrap();
+        transaction
+            .execute(
+                "INSERT INTO starknet_blocks (number, root) VALUES (0, ?)",
+                [root],
+            )
+            .unwrap();
+
+        let tree = StorageCommitmentTree::at_block(&transaction, BlockNumber::GENESIS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.get(address).unwrap(), Some(state_hash));
+
+        let unknown =
+            StorageCommitmentTree::at_block(&transaction, BlockNumber::new_or_panic(1)).unwrap();
+        assert!(unknown.is_none());
+    }
+
+    #[test]
+    fn contract_storage_at_block() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let key = StorageAddress::new_or_panic(felt!("0x5"));
+        let value = StorageValue(felt!("0x6"));
+        let mut storage = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        storage.set(key, value).unwrap();
+        let contract_root = storage.commit_and_persist_changes().unwrap();
+
+        let state_hash = ContractStateHash(felt!("0x1234"));
+        ContractsStateTable::upsert(
+            &transaction,
+            state_hash,
+            ClassHash(felt!("0x7")),
+            contract_root,
+            ContractNonce::ZERO,
+        )
+        .unwrap();
+
+        let deployed = ContractAddress::new_or_panic(felt!("0x1"));
+        let mut tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        tree.set(deployed, state_hash).unwrap();
+        let root = tree.commit_and_persist_changes().unwrap();
+        transaction
+            .execute(
+                "INSERT INTO starknet_blocks (number, root) VALUES (0, ?)",
+                [root],
+            )
+            .unwrap();
+
+        let storage = ContractsStorageTree::at_block(&transaction, deployed, BlockNumber::GENESIS)
+            .unwrap()
+            .unwrap();
+        assert_eq!(storage.root(), contract_root);
+        assert_eq!(storage.get(key).unwrap(), Some(value));
+
+        let undeployed = ContractAddress::new_or_panic(felt!("0x2"));
+        let storage =
+            ContractsStorageTree::at_block(&transaction, undeployed, BlockNumber::GENESIS).unwrap();
+        assert!(storage.is_none());
+
+        let unknown_block =
+            ContractsStorageTree::at_block(&transaction, deployed, BlockNumber::new_or_panic(1))
+                .unwrap();
+        assert!(unknown_block.is_none());
+    }
+
+    #[test]
+    fn state_reader() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let key = StorageAddress::new_or_panic(felt!("0x5"));
+        let value = StorageValue(felt!("0x6"));
+        let mut storage = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        storage.set(key, value).unwrap();
+        let contract_root = storage.commit_and_persist_changes().unwrap();
+
+        // The state hash is only used as a lookup key, so its actual value is irrelevant.
+        let state_hash = ContractStateHash(felt!("0x1234"));
+        let class_hash = ClassHash(felt!("0x7"));
+        let nonce = ContractNonce(felt!("0x8"));
+        ContractsStateTable::upsert(&transaction, state_hash, class_hash, contract_root, nonce)
+            .unwrap();
+
+        let deployed = ContractAddress::new_or_panic(felt!("0x1"));
+        let mut tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        tree.set(deployed, state_hash).unwrap();
+        let root = tree.commit_and_persist_changes().unwrap();
+
+        let tree = StorageCommitmentTree::load(&transaction, root);
+        assert_eq!(tree.get_storage(deployed, key).unwrap(), value);
+        assert_eq!(
+            tree.get_storage(deployed, StorageAddress::new_or_panic(felt!("0x9")))
+                .unwrap(),
+            StorageValue::ZERO
+        );
+        assert_eq!(tree.get_class_hash(deployed).unwrap(), class_hash);
+        assert_eq!(tree.get_nonce(deployed).unwrap(), nonce);
+
+        let undeployed = ContractAddress::new_or_panic(felt!("0x2"));
+        assert_eq!(
+            tree.get_storage(undeployed, key).unwrap(),
+            StorageValue::ZERO
+        );
+        assert_eq!(tree.get_class_hash(undeployed).unwrap(), ClassHash::ZERO);
+        assert_eq!(tree.get_nonce(undeployed).unwrap(), ContractNonce::ZERO);
+    }
+
+    #[test]
+    fn root_matches_commit() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let mut storage = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        assert_eq!(storage.root(), ContractRoot::ZERO);
+        storage
+            .set(
+                StorageAddress::new_or_panic(felt!("0x1")),
+                StorageValue(felt!("0x2")),
+            )
+            .unwrap();
+        let speculative = storage.root();
+        assert_ne!(speculative, ContractRoot::ZERO);
+        let root = storage.commit_and_persist_changes().unwrap();
+        assert_eq!(root, speculative);
+        assert_eq!(ContractsStorageTree::load(&transaction, root).root(), root);
+
+        let mut tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        assert_eq!(tree.root(), StorageCommitment::ZERO);
+        tree.set(
+            ContractAddress::new_or_panic(felt!("0x1")),
+            ContractStateHash(felt!("0x2")),
+        )
+        .unwrap();
+        let speculative = tree.root();
+        assert_ne!(speculative, StorageCommitment::ZERO);
+        let root = tree.commit_and_persist_changes().unwrap();
+        assert_eq!(root, speculative);
+        assert_eq!(StorageCommitmentTree::load(&transaction, root).root(), root);
+    }
+
+    #[test]
+    fn batch_set_matches_set() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let updates = (1..=50u64)
+            .map(|i| {
+                (
+                    ContractAddress::new_or_panic(spread_key(i)),
+                    ContractStateHash(Felt::from_u64(i)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Start from a persisted tree which the updates partly overwrite and delete from.
+        let mut initial = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+        for i in (0..=100u64).step_by(3) {
+            initial
+                .set(
+                    ContractAddress::new_or_panic(spread_key(i)),
+                    ContractStateHash(Felt::from_u64(i + 1000)),
+                )
+                .unwrap();
+        }
+        let initial = initial.commit_and_persist_changes().unwrap();
+
+        let updates = updates
+            .into_iter()
+            .map(|(address, value)| match value.0 == Felt::from_u64(10) {
+                true => (address, ContractStateHash::ZERO),
+                false => (address, value),
+            })
+            .collect::<Vec<_>>();
+
+        let mut expected = StorageCommitmentTree::load(&transaction, initial);
+        for (address, value) in &updates {
+            expected.set(*address, *value).unwrap();
+        }
+        let expected = expected.commit_and_persist_changes().unwrap();
+
+        // The result must not depend on the order of the updates.
+        let mut reversed = updates.clone();
+        reversed.reverse();
+        let mut interleaved = updates.iter().step_by(2).copied().collect::<Vec<_>>();
+        interleaved.extend(updates.iter().skip(1).step_by(2).copied());
+
+        for updates in [updates.clone(), reversed, interleaved] {
+            let mut tree = StorageCommitmentTree::load(&transaction, initial);
+            tree.batch_set(updates).unwrap();
+            assert_eq!(tree.commit_and_persist_changes().unwrap(), expected);
+        }
+
+        // Only the last update of a contract counts.
+        let mut overwritten = updates
+            .iter()
+            .map(|(address, _)| (*address, ContractStateHash(felt!("0xdead"))))
+            .collect::<Vec<_>>();
+        overwritten.extend(updates);
+        let mut tree = StorageCommitmentTree::load(&transaction, initial);
+        tree.batch_set(overwritten).unwrap();
+        assert_eq!(tree.commit_and_persist_changes().unwrap(), expected);
+    }
+
+    #[test]
+    fn delete_all_keys_gives_empty_root() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let addresses = (1..=10u64)
+            .map(|i| StorageAddress::new_or_panic(spread_key(i)))
+            .collect::<Vec<_>>();
+
+        let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        for address in &addresses {
+            tree.set(*address, StorageValue(felt!("0x1"))).unwrap();
+        }
+        let root = tree.commit_and_persist_changes().unwrap();
+
+        let mut tree = ContractsStorageTree::load(&transaction, root);
+        for address in &addresses {
+            tree.delete(*address).unwrap();
+            assert_eq!(tree.get(*address).unwrap(), None);
+        }
+        assert_eq!(
+            tree.commit_and_persist_changes().unwrap(),
+            ContractRoot::ZERO
+        );
+    }
+
+    #[test]
+    fn leaves_in_key_order() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        assert_eq!(tree.leaves().count(), 0);
+
+        let mut expected = (1..=40u64)
+            .map(|i| (spread_key(i), Felt::from_u64(i)))
+            .collect::<Vec<_>>();
+        for (key, value) in &expected {
+            tree.set(StorageAddress::new_or_panic(*key), StorageValue(*value))
+                .unwrap();
+        }
+        let root = tree.commit_and_persist_changes().unwrap();
+        expected.sort();
+
+        // Load the tree from storage so that the iteration has to resolve the nodes.
+        let tree = ContractsStorageTree::load(&transaction, root);
+        let leaves = tree
+            .leaves()
+            .map(|leaf| {
+                let (key, value) = leaf.unwrap();
+                (Felt::from_bits(&key).unwrap(), value)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(leaves, expected);
+    }
+
+    #[test]
+    fn delete_restores_previous_root() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+
+        let kept = StorageAddress::new_or_panic(felt!("0x10"));
+        let deleted = StorageAddress::new_or_panic(felt!("0x11"));
+
+        let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+        tree.set(kept, StorageValue(felt!("0x1"))).unwrap();
+        let expected = tree.commit_and_persist_changes().unwrap();
+
+        let mut tree = ContractsStorageTree::load(&transaction, expected);
+        tree.set(deleted, StorageValue(felt!("0x2"))).unwrap();
+        let root = tree.commit_and_persist_changes().unwrap();
+        assert_ne!(root, expected);
+
+        let mut tree = ContractsStorageTree::load(&transaction, root);
+        tree.delete(deleted).unwrap();
+        assert_eq!(tree.get(deleted).unwrap(), None);
+        assert_eq!(tree.commit_and_persist_changes().unwrap(), expected);
+    }
+
+    mod get_proof {
+        use super::*;
+        use crate::Node;
+
+        /// Follows the proof from `root` along `address`, returning the leaf value it leads to.
+        fn proven_value(root: ContractRoot, address: StorageAddress, proof: &[Node]) -> Felt {
+            let mut expected_hash = root.0;
+            let mut remaining_path = address.view_bits();
+
+            for node in proof {
+                assert_eq!(node.hash::<PedersenHash>(), expected_hash);
+                match node {
+                    Node::Binary { left, right } => {
+                        expected_hash = if remaining_path[0] { *right } else { *left };
+                        remaining_path = &remaining_path[1..];
+                    }
+                    Node::Edge { child, path } => {
+                        assert_eq!(path, &remaining_path[..path.len()]);
+                        expected_hash = *child;
+                        remaining_path = &remaining_path[path.len()..];
+                    }
+                }
+            }
+
+            assert!(remaining_path.is_empty());
+            expected_hash
+        }
+
+        #[test]
+        fn deleted_key() {
+            let mut conn = open_database();
+            let transaction = conn.transaction().unwrap();
+
+            let kept = StorageAddress::new_or_panic(felt!("0x10"));
+            let deleted = StorageAddress::new_or_panic(felt!("0x11"));
+
+            let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+            tree.set(kept, StorageValue(felt!("0x1"))).unwrap();
+            let root = tree.commit_and_persist_changes().unwrap();
+            let never_set = ContractsStorageTree::load(&transaction, root)
+                .get_proof(&deleted)
+                .unwrap();
+
+            let mut tree = ContractsStorageTree::load(&transaction, root);
+            tree.set(deleted, StorageValue(felt!("0x2"))).unwrap();
+            tree.delete(deleted).unwrap();
+            let root = tree.commit_and_persist_changes().unwrap();
+
+            // The non-membership proof is the same as if the key had never been set.
+            let tree = ContractsStorageTree::load(&transaction, root);
+            assert_eq!(tree.get_proof(&deleted).unwrap(), never_set);
+            assert_eq!(
+                proven_value(root, kept, &tree.get_proof(&kept).unwrap()),
+                felt!("0x1")
+            );
+        }
+
+        #[test]
+        fn zero_max_and_mixed_node_paths() {
+            let mut conn = open_database();
+            let transaction = conn.transaction().unwrap();
+
+            let zero = StorageAddress::new_or_panic(felt!("0x0"));
+            // Only differs from zero in the last bit, so the path to it passes through
+            // the root binary node, a long edge and a final binary node.
+            let one = StorageAddress::new_or_panic(felt!("0x1"));
+            let max = StorageAddress::new_or_panic(felt!(
+                "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+            ));
+
+            let values = [
+                (zero, StorageValue(felt!("0xa"))),
+                (one, StorageValue(felt!("0xb"))),
+                (max, StorageValue(felt!("0xc"))),
+            ];
+
+            let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+            for (address, value) in values {
+                tree.set(address, value).unwrap();
+            }
+            let root = tree.commit_and_persist_changes().unwrap();
+
+            let tree = ContractsStorageTree::load(&transaction, root);
+            for (address, value) in values {
+                let proof = tree.get_proof(&address).unwrap();
+                assert_eq!(proven_value(root, address, &proof), value.0);
+            }
+
+            let proof = tree.get_proof(&zero).unwrap();
+            assert!(proof.iter().any(|node| matches!(node, Node::Binary { .. })));
+            assert!(proof.iter().any(|node| matches!(node, Node::Edge { .. })));
+        }
+    }
+
+    mod load_checked {
+        use super::*;
+
+        #[test]
+        fn existing_and_empty_roots() {
+            let mut conn = open_database();
+            let transaction = conn.transaction().unwrap();
+
+            ContractsStorageTree::load_checked(&transaction, ContractRoot::ZERO).unwrap();
+
+            let mut tree = ContractsStorageTree::load(&transaction, ContractRoot::ZERO);
+            tree.set(
+                StorageAddress::new_or_panic(felt!("0x1")),
+                StorageValue(felt!("0x2")),
+            )
+            .unwrap();
+            let root = tree.commit_and_persist_changes().unwrap();
+
+            ContractsStorageTree::load_checked(&transaction, root).unwrap();
+        }
+
+        #[test]
+        fn missing_root() {
+            let mut conn = open_database();
+            let transaction = conn.transaction().unwrap();
+
+            let root = ContractRoot(felt!("0x1234"));
+            let error = ContractsStorageTree::load_checked(&transaction, root)
+                .err()
+                .unwrap();
+            let error = error.downcast::<RootNotFound>().unwrap();
+            assert_eq!(error.root, root);
+        }
+    }
 }
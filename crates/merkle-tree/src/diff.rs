@@ -0,0 +1,248 @@
+//! State diff proofs for the [StorageCommitmentTree](crate::StorageCommitmentTree), which prove
+//! that applying a set of contract updates to one storage commitment results in another.
+use crate::storage::Storage;
+use crate::tree::MerkleTree;
+use crate::{Node, PedersenHash};
+use pathfinder_common::{ContractAddress, ContractStateHash, StorageCommitment};
+use stark_hash::Felt;
+use std::collections::HashMap;
+
+/// Proves the transition between two storage commitments caused by updating the state of
+/// some contracts.
+///
+/// Both lists contain the updated contracts in the same order, together with their state
+/// before and after the update and the proofs returned by
+/// [StorageCommitmentTree::get_proof](crate::StorageCommitmentTree::get_proof) in the old
+/// and new tree respectively. Contracts which did not exist have a [ContractStateHash] of zero.
+///
+/// Removing a contract merges its sibling subtree into the parent node, so its old proof must
+/// also contain the sibling's node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateDiffProof {
+    pub old_leaves: Vec<(ContractAddress, ContractStateHash, Vec<Node>)>,
+    pub new_leaves: Vec<(ContractAddress, ContractStateHash, Vec<Node>)>,
+}
+
+/// Verifies that updating the contracts of `old_root` to the new states in the [StateDiffProof]
+/// results in `new_root`.
+///
+/// The old tree is rebuilt from the proofs' nodes as far as the updates require it, after which
+/// the updates are applied and the resulting root is compared to `new_root`. All other subtrees
+/// are only known by their hash and hence remain unchanged.
+pub fn verify(
+    old_root: StorageCommitment,
+    new_root: StorageCommitment,
+    proof: &StateDiffProof,
+) -> bool {
+    if proof.old_leaves.len() != proof.new_leaves.len() {
+        return false;
+    }
+    let same_contracts = proof
+        .old_leaves
+        .iter()
+        .zip(&proof.new_leaves)
+        .all(|(old, new)| old.0 == new.0);
+    if !same_contracts {
+        return false;
+    }
+
+    // Nodes are only ever looked up by their hash, so the nodes of both proofs can be used
+    // to rebuild either tree.
+    let storage = ProofStorage(
+        proof
+            .old_leaves
+            .iter()
+            .chain(&proof.new_leaves)
+            .flat_map(|(_, _, nodes)| nodes)
+            .map(|node| (node.hash::<PedersenHash>(), node))
+            .collect(),
+    );
+
+    let mut tree = MerkleTree::<PedersenHash, 251>::new(old_root.0);
+    if !has_states(&tree, &storage, &proof.old_leaves) {
+        return false;
+    }
+    for (address, state_hash, _) in &proof.new_leaves {
+        if tree
+            .set(&storage, address.view_bits(), state_hash.0)
+            .is_err()
+        {
+            return false;
+        }
+    }
+    if tree.root_without_commit() != new_root.0 {
+        return false;
+    }
+
+    has_states(&MerkleTree::new(new_root.0), &storage, &proof.new_leaves)
+}
+
+/// Checks that the contracts have the given states in the `tree`.
+fn has_states(
+    tree: &MerkleTree<PedersenHash, 251>,
+    storage: &ProofStorage<'_>,
+    leaves: &[(ContractAddress, ContractStateHash, Vec<Node>)],
+) -> bool {
+    leaves.iter().all(
+        |(address, state_hash, _)| match tree.get(storage, address.view_bits()) {
+            Ok(value) => value.unwrap_or(Felt::ZERO) == state_hash.0,
+            Err(_) => false,
+        },
+    )
+}
+
+/// [Storage] backed by the nodes of a [StateDiffProof].
+struct ProofStorage<'a>(HashMap<Felt, &'a Node>);
+
+impl Storage for ProofStorage<'_> {
+    type Error = std::convert::Infallible;
+
+    fn get(&self, node: &Felt) -> Result<Option<Node>, Self::Error> {
+        Ok(self.0.get(node).map(|&node| node.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StorageCommitmentTree;
+    use pathfinder_common::felt;
+
+    fn address(value: u64) -> ContractAddress {
+        ContractAddress::new_or_panic(Felt::from_u64(value))
+    }
+
+    fn state_hash(value: u64) -> ContractStateHash {
+        ContractStateHash(Felt::from_u64(value))
+    }
+
+    /// Applies the updates to the tree with the given `root` and returns the new root together
+    /// with the [StateDiffProof] of the transition.
+    fn apply(
+        transaction: &rusqlite::Transaction<'_>,
+        root: StorageCommitment,
+        updates: &[(ContractAddress, ContractStateHash)],
+    ) -> (StorageCommitment, StateDiffProof) {
+        let leaves = |tree: &StorageCommitmentTree<'_>| {
+            updates
+                .iter()
+                .map(|(address, _)| {
+                    let state_hash = tree
+                        .get(*address)
+                        .unwrap()
+                        .unwrap_or(ContractStateHash(Felt::ZERO));
+                    (*address, state_hash, tree.get_proof(address).unwrap())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let old_tree = StorageCommitmentTree::load(transaction, root);
+        let old_leaves = leaves(&old_tree);
+
+        let mut tree = StorageCommitmentTree::load(transaction, root);
+        for (address, state_hash) in updates {
+            tree.set(*address, *state_hash).unwrap();
+        }
+        let new_root = tree.commit_and_persist_changes().unwrap();
+
+        let new_tree = StorageCommitmentTree::load(transaction, new_root);
+        let new_leaves = leaves(&new_tree);
+
+        (
+            new_root,
+            StateDiffProof {
+                old_leaves,
+                new_leaves,
+            },
+        )
+    }
+
+    fn setup(transaction: &rusqlite::Transaction<'_>) -> StorageCommitment {
+        transaction
+            .execute(
+                "CREATE TABLE tree_global (hash BLOB PRIMARY KEY, data BLOB)",
+                [],
+            )
+            .unwrap();
+
+        let updates = (1..=0x10u64)
+            .map(|i| (address(i * 0x10), state_hash(i)))
+            .collect::<Vec<_>>();
+        apply(transaction, StorageCommitment::ZERO, &updates).0
+    }
+
+    #[test]
+    fn transitions() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+
+        let (first_root, proof) = apply(
+            &transaction,
+            StorageCommitment::ZERO,
+            &[(address(0x10), state_hash(1))],
+        );
+        assert!(verify(StorageCommitment::ZERO, first_root, &proof));
+
+        let updates = [
+            // Updated contracts.
+            (address(0x20), state_hash(0x200)),
+            (address(0x70), state_hash(0x700)),
+            // New contracts, both next to and far away from the existing ones.
+            (address(0x21), state_hash(0x21)),
+            (
+                ContractAddress::new_or_panic(felt!(
+                    "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+                )),
+                state_hash(0x7ff),
+            ),
+        ];
+        let (new_root, proof) = apply(&transaction, root, &updates);
+        assert!(verify(root, new_root, &proof));
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+
+        let updates = [
+            (address(0x20), state_hash(0x200)),
+            (address(0x21), state_hash(0x21)),
+        ];
+        let (new_root, proof) = apply(&transaction, root, &updates);
+
+        // The roots must match the proof.
+        assert!(!verify(new_root, root, &proof));
+        assert!(!verify(root, root, &proof));
+
+        let mut old_state = proof.clone();
+        old_state.old_leaves[0].1 = state_hash(0x1234);
+        assert!(!verify(root, new_root, &old_state));
+
+        let mut new_state = proof.clone();
+        new_state.new_leaves[1].1 = state_hash(0x1234);
+        assert!(!verify(root, new_root, &new_state));
+
+        // Omitting an update changes the resulting root.
+        let mut omitted = proof.clone();
+        omitted.old_leaves.pop();
+        omitted.new_leaves.pop();
+        assert!(!verify(root, new_root, &omitted));
+
+        let mut mismatched = proof.clone();
+        mismatched.new_leaves.swap(0, 1);
+        assert!(!verify(root, new_root, &mismatched));
+
+        let mut missing_nodes = proof;
+        for (_, _, nodes) in missing_nodes
+            .old_leaves
+            .iter_mut()
+            .chain(&mut missing_nodes.new_leaves)
+        {
+            nodes.clear();
+        }
+        assert!(!verify(root, new_root, &missing_nodes));
+    }
+}
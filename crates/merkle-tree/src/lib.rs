@@ -1,23 +1,31 @@
 pub mod contract_state;
+pub mod diff;
 pub mod merkle_node;
 pub mod tree;
 
 mod class;
 mod contract;
 mod hash;
+mod range_proof;
 mod storage;
+mod storage_proof;
 mod transaction;
 
-use bitvec::prelude::Msb0;
+use bitvec::prelude::{BitSlice, Msb0};
 use bitvec::vec::BitVec;
 use stark_hash::Felt;
 
 pub use class::ClassCommitmentTree;
-pub use contract::{ContractsStorageTree, StorageCommitmentTree};
+pub use contract::{ContractsStorageTree, StateReader, StorageCommitmentTree};
 pub use hash::{Hash, PedersenHash, PoseidonHash};
-pub use storage::Storage;
+pub use range_proof::{verify_range_proof, RangeProof};
+pub use storage::{MutableStorage, Storage};
+pub use storage_proof::{verify_contract_storage_proof, ContractData, ContractStorageProof};
 pub use transaction::TransactionOrEventTree;
 
+/// Height of the Starknet trees, i.e. the length of their keys in bits.
+pub(crate) const HEIGHT: usize = 251;
+
 /// A node of a committed [MerkleTree](tree::MerkleTree).
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -41,3 +49,75 @@ impl Node {
         }
     }
 }
+
+/// What a proof verified by [verify_proof] demonstrates about its key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Membership {
+    /// The key has the expected value.
+    Member,
+    /// The key is not part of the tree.
+    NonMember,
+}
+
+/// Verifies a proof for `key` in the [PedersenHash] tree with the given `root`, as returned by
+/// [MerkleTree::get_proof](tree::MerkleTree::get_proof).
+///
+/// Starting at the root, each node's hash must match the hash expected by its parent, and the
+/// nodes must follow the path of `key`. The proof demonstrates
+/// - [Membership::Member] if it reaches the leaf of `key`, which must be `value`, or
+/// - [Membership::NonMember] if its last node is an edge which leaves the path of `key`, or the
+///   tree is empty. `value` is not checked in this case.
+///
+/// Returns [None] for any other proof, including proofs which are too short, continue past the
+/// point where they diverge from `key`, or contain a hash mismatch. Keys which are not exactly
+/// 251 bits long are rejected as well, as a shorter one could pass off an inner node as a leaf.
+pub fn verify_proof(
+    root: Felt,
+    key: &BitSlice<Msb0, u8>,
+    value: Felt,
+    proof: &[Node],
+) -> Option<Membership> {
+    if key.len() != HEIGHT {
+        return None;
+    }
+
+    if root == Felt::ZERO && proof.is_empty() {
+        return Some(Membership::NonMember);
+    }
+
+    let mut expected = root;
+    let mut remaining = key;
+
+    for (i, node) in proof.iter().enumerate() {
+        if node.hash::<PedersenHash>() != expected {
+            return None;
+        }
+
+        match node {
+            Node::Binary { left, right } => {
+                if remaining.is_empty() {
+                    return None;
+                }
+
+                expected = if remaining[0] { *right } else { *left };
+                remaining = &remaining[1..];
+            }
+            Node::Edge { child, path } => {
+                if remaining.len() < path.len() {
+                    return None;
+                }
+
+                if remaining[..path.len()] != path[..] {
+                    // The key would have to be in this edge's subtree, but it isn't.
+                    let is_last = i == proof.len() - 1;
+                    return is_last.then_some(Membership::NonMember);
+                }
+
+                expected = *child;
+                remaining = &remaining[path.len()..];
+            }
+        }
+    }
+
+    (remaining.is_empty() && expected == value).then_some(Membership::Member)
+}
@@ -0,0 +1,323 @@
+//! Range proofs for the [StorageCommitmentTree](crate::StorageCommitmentTree), which prove the
+//! state of all contracts whose addresses lie within a range.
+use crate::{Node, PedersenHash, HEIGHT};
+use anyhow::Context;
+use bitvec::{prelude::BitSlice, prelude::BitVec, prelude::Msb0};
+use pathfinder_common::{ContractAddress, ContractStateHash, StorageCommitment};
+use stark_hash::Felt;
+use std::collections::HashMap;
+
+/// Proves the state of all contracts with addresses in an inclusive range, as generated by
+/// [StorageCommitmentTree::get_range_proof](crate::StorageCommitmentTree::get_range_proof).
+///
+/// The proof consists of the proofs of the range's boundaries as returned by
+/// [StorageCommitmentTree::get_proof](crate::StorageCommitmentTree::get_proof), i.e. the
+/// nodes along the path from the root towards `start` and `end`, and all the leaves within
+/// the range in ascending address order.
+///
+/// Every subtree of the tree either lies entirely within the range, entirely outside of it,
+/// or contains one of the boundaries. The hashes of subtrees within the range can be computed
+/// from the leaves, and the nodes containing a boundary are part of the boundary proofs. This
+/// lets a verifier recompute the root, which only matches if no leaf was omitted, see
+/// [verify_range_proof].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeProof {
+    pub start_proof: Vec<Node>,
+    pub end_proof: Vec<Node>,
+    pub leaves: Vec<(ContractAddress, ContractStateHash)>,
+}
+
+/// Verifies that the [RangeProof] contains exactly the contracts with addresses in
+/// `start..=end` of the tree with the given `root`.
+pub fn verify_range_proof(
+    root: StorageCommitment,
+    start: ContractAddress,
+    end: ContractAddress,
+    proof: &RangeProof,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(start <= end, "Range start is after its end");
+
+    for pair in proof.leaves.windows(2) {
+        anyhow::ensure!(pair[0].0 < pair[1].0, "Leaves are not in ascending order");
+    }
+    for (address, _) in &proof.leaves {
+        anyhow::ensure!(
+            start <= *address && *address <= end,
+            "Leaf is outside of the range"
+        );
+    }
+
+    if root == StorageCommitment::ZERO {
+        anyhow::ensure!(proof.leaves.is_empty(), "Leaves of an empty tree");
+        return Ok(());
+    }
+
+    let mut verifier = Verifier {
+        start: start.view_bits(),
+        end: end.view_bits(),
+        nodes: proof
+            .start_proof
+            .iter()
+            .chain(&proof.end_proof)
+            .map(|node| (node.hash::<PedersenHash>(), node))
+            .collect(),
+        leaves: proof
+            .leaves
+            .iter()
+            .map(|(address, state_hash)| (address.view_bits(), state_hash.0))
+            .collect(),
+        verified: 0,
+    };
+
+    verifier.verify_subtree(root.0, &mut BitVec::new())?;
+
+    // Leaves which are not part of any subtree of the tree are never verified.
+    anyhow::ensure!(
+        verifier.verified == verifier.leaves.len(),
+        "Leaves are not part of the tree"
+    );
+
+    Ok(())
+}
+
+/// How a subtree relates to a range of keys.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Overlap {
+    /// None of the subtree's keys are in the range.
+    Outside,
+    /// All of the subtree's keys are in the range.
+    Inside,
+    /// The subtree contains one of the range's boundaries.
+    Partial,
+}
+
+impl Overlap {
+    /// Determines how the subtree at `prefix` relates to the keys in `start..=end`.
+    pub(crate) fn of(
+        prefix: &BitSlice<Msb0, u8>,
+        start: &BitSlice<Msb0, u8>,
+        end: &BitSlice<Msb0, u8>,
+    ) -> Self {
+        let depth = prefix.len();
+        let (lowest, highest) = (&start[..depth], &end[..depth]);
+
+        if prefix < lowest || prefix > highest {
+            return Overlap::Outside;
+        }
+
+        // The subtree spans from `prefix` followed by all zeros to `prefix` followed by all ones.
+        let above_start = prefix > lowest || start[depth..].not_any();
+        let below_end = prefix < highest || end[depth..].all();
+
+        if above_start && below_end {
+            Overlap::Inside
+        } else {
+            Overlap::Partial
+        }
+    }
+}
+
+struct Verifier<'a> {
+    start: &'a BitSlice<Msb0, u8>,
+    end: &'a BitSlice<Msb0, u8>,
+    /// The boundary proofs' nodes by their hash.
+    nodes: HashMap<Felt, &'a Node>,
+    leaves: Vec<(&'a BitSlice<Msb0, u8>, Felt)>,
+    /// Number of leaves verified so far. Subtrees are verified from left to right, so these
+    /// are always the first leaves.
+    verified: usize,
+}
+
+impl Verifier<'_> {
+    /// Verifies the subtree at `prefix` with the given `hash`.
+    fn verify_subtree(&mut self, hash: Felt, prefix: &mut BitVec<Msb0, u8>) -> anyhow::Result<()> {
+        match Overlap::of(prefix, self.start, self.end) {
+            Overlap::Outside => Ok(()),
+            Overlap::Inside => {
+                let remaining = &self.leaves[self.verified..];
+                let count = remaining
+                    .iter()
+                    .take_while(|(key, _)| key[..prefix.len()] == prefix[..])
+                    .count();
+                anyhow::ensure!(count > 0, "Leaves are missing from the range");
+
+                let computed = subtree_hash(&remaining[..count], prefix.len());
+                anyhow::ensure!(computed == hash, "Leaves do not match the tree");
+
+                self.verified += count;
+                Ok(())
+            }
+            Overlap::Partial => {
+                let node = *self
+                    .nodes
+                    .get(&hash)
+                    .context("Boundary proofs are missing a node")?;
+
+                match node {
+                    Node::Binary { left, right } => {
+                        prefix.push(false);
+                        self.verify_subtree(*left, prefix)?;
+                        prefix.pop();
+
+                        prefix.push(true);
+                        self.verify_subtree(*right, prefix)?;
+                        prefix.pop();
+                    }
+                    Node::Edge { child, path } => {
+                        anyhow::ensure!(
+                            prefix.len() + path.len() <= HEIGHT,
+                            "Edge extends beyond the tree's height"
+                        );
+
+                        let depth = prefix.len();
+                        prefix.extend_from_bitslice(path);
+                        self.verify_subtree(*child, prefix)?;
+                        prefix.truncate(depth);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Computes the hash of the subtree at `depth` which contains exactly the given `leaves`.
+///
+/// The leaves must be non-empty, unique, sorted and share the same path up to `depth`.
+fn subtree_hash(leaves: &[(&BitSlice<Msb0, u8>, Felt)], depth: usize) -> Felt {
+    if depth == HEIGHT {
+        return leaves[0].1;
+    }
+
+    // The leaves are sorted, so the first and last leaves have the shortest common path.
+    let first = leaves[0].0;
+    let last = leaves[leaves.len() - 1].0;
+    let common = (depth..HEIGHT).take_while(|&i| first[i] == last[i]).count();
+
+    if common > 0 {
+        Node::Edge {
+            child: subtree_hash(leaves, depth + common),
+            path: first[depth..depth + common].to_bitvec(),
+        }
+        .hash::<PedersenHash>()
+    } else {
+        let split = leaves.partition_point(|(key, _)| !key[depth]);
+        Node::Binary {
+            left: subtree_hash(&leaves[..split], depth + 1),
+            right: subtree_hash(&leaves[split..], depth + 1),
+        }
+        .hash::<PedersenHash>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StorageCommitmentTree;
+    use pathfinder_common::felt;
+
+    /// Creates a tree with a contract at every multiple of `0x10` up to `0x100`.
+    fn setup(transaction: &rusqlite::Transaction<'_>) -> StorageCommitment {
+        transaction
+            .execute(
+                "CREATE TABLE tree_global (hash BLOB PRIMARY KEY, data BLOB)",
+                [],
+            )
+            .unwrap();
+
+        let mut tree = StorageCommitmentTree::load(transaction, StorageCommitment::ZERO);
+        for i in 1..=0x10u64 {
+            tree.set(
+                ContractAddress::new_or_panic(Felt::from_u64(i * 0x10)),
+                ContractStateHash(Felt::from_u64(i)),
+            )
+            .unwrap();
+        }
+        tree.commit_and_persist_changes().unwrap()
+    }
+
+    fn address(value: u64) -> ContractAddress {
+        ContractAddress::new_or_panic(Felt::from_u64(value))
+    }
+
+    #[test]
+    fn ranges() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+        let tree = StorageCommitmentTree::load(&transaction, root);
+
+        let max = ContractAddress::new_or_panic(felt!(
+            "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        ));
+
+        // (start, end, number of leaves)
+        let ranges = [
+            // Empty ranges, both in between and beyond the existing contracts.
+            (address(0x11), address(0x1f), 0),
+            (address(0x101), max, 0),
+            // Single element ranges.
+            (address(0x20), address(0x20), 1),
+            (address(0x1f), address(0x21), 1),
+            (address(0x100), max, 1),
+            (address(0x25), address(0x75), 5),
+            (address(0), max, 16),
+        ];
+
+        for (start, end, count) in ranges {
+            let proof = tree.get_range_proof(start, end).unwrap();
+            assert_eq!(proof.leaves.len(), count);
+            verify_range_proof(root, start, end, &proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn empty_tree() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        transaction
+            .execute(
+                "CREATE TABLE tree_global (hash BLOB PRIMARY KEY, data BLOB)",
+                [],
+            )
+            .unwrap();
+        let tree = StorageCommitmentTree::load(&transaction, StorageCommitment::ZERO);
+
+        let proof = tree.get_range_proof(address(0), address(0x100)).unwrap();
+        assert!(proof.leaves.is_empty());
+        verify_range_proof(StorageCommitment::ZERO, address(0), address(0x100), &proof).unwrap();
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+        let tree = StorageCommitmentTree::load(&transaction, root);
+
+        let (start, end) = (address(0x25), address(0x75));
+        let proof = tree.get_range_proof(start, end).unwrap();
+
+        let mut omitted = proof.clone();
+        omitted.leaves.remove(2);
+        verify_range_proof(root, start, end, &omitted).unwrap_err();
+
+        let mut modified = proof.clone();
+        modified.leaves[2].1 = ContractStateHash(felt!("0x1234"));
+        verify_range_proof(root, start, end, &modified).unwrap_err();
+
+        let mut added = proof.clone();
+        added
+            .leaves
+            .insert(2, (address(0x41), ContractStateHash(felt!("0x1"))));
+        verify_range_proof(root, start, end, &added).unwrap_err();
+
+        let mut missing_boundary = proof.clone();
+        missing_boundary.end_proof.pop();
+        verify_range_proof(root, start, end, &missing_boundary).unwrap_err();
+
+        // A proof is only valid for the range it was generated for.
+        verify_range_proof(root, start, address(0x85), &proof).unwrap_err();
+    }
+}
@@ -11,6 +11,14 @@ pub trait Storage {
     fn get(&self, node: &Felt) -> Result<Option<Node>, Self::Error>;
 }
 
+/// [Storage] which can also persist nodes, such as those [added](crate::tree::Update::added)
+/// by committing a [Merkle tree](crate::tree::MerkleTree).
+///
+/// Implement this to back the trees with a custom storage, see [Update::persist](crate::tree::Update::persist).
+pub trait MutableStorage: Storage {
+    fn insert(&self, hash: &Felt, node: &Node) -> Result<(), Self::Error>;
+}
+
 /// Wrapper around [anyhow::Error].
 #[derive(thiserror::Error, Debug)]
 #[error(transparent)]
@@ -93,6 +101,9 @@ impl rusqlite::types::ToSql for Node {
     }
 }
 
+/// Maximum number of nodes written by a single insert statement of a [define_sqlite_storage] adapter.
+pub(crate) const INSERT_BATCH_SIZE: usize = 500;
+
 /// This macro defines a [Storage] adapter for a Sqlite table.
 ///
 /// The table schema is expected to already be created and should match:
@@ -116,18 +127,33 @@ macro_rules! define_sqlite_storage {
                 Self(tx)
             }
 
+            /// Inserts the nodes using multi-row statements of up to
+            /// [INSERT_BATCH_SIZE](crate::storage::INSERT_BATCH_SIZE) rows each.
             #[allow(dead_code)]
-            fn insert(&self, hash: &stark_hash::Felt, node: &$crate::Node) -> anyhow::Result<()> {
-                self.0
-                    .execute(
-                        concat!(
-                            "INSERT OR IGNORE INTO ",
-                            $table,
-                            " (hash, data) VALUES (?, ?)"
-                        ),
-                        rusqlite::params![hash.as_be_bytes(), node],
-                    )
-                    .context("Inserting node into tree_class table")?;
+            fn insert_batch(
+                &self,
+                nodes: &std::collections::HashMap<stark_hash::Felt, $crate::Node>,
+            ) -> anyhow::Result<()> {
+                let nodes = nodes.iter().collect::<Vec<_>>();
+
+                for batch in nodes.chunks($crate::storage::INSERT_BATCH_SIZE) {
+                    let sql = format!(
+                        concat!("INSERT OR IGNORE INTO ", $table, " (hash, data) VALUES {}"),
+                        vec!["(?, ?)"; batch.len()].join(", ")
+                    );
+                    let params = batch.iter().flat_map(|(hash, node)| {
+                        [
+                            hash.as_be_bytes() as &dyn rusqlite::ToSql,
+                            *node as &dyn rusqlite::ToSql,
+                        ]
+                    });
+
+                    self.0
+                        .prepare_cached(&sql)
+                        .context(concat!("Preparing insert into ", $table))?
+                        .execute(rusqlite::params_from_iter(params))
+                        .context(concat!("Inserting nodes into ", $table))?;
+                }
 
                 Ok(())
             }
@@ -150,5 +176,25 @@ macro_rules! define_sqlite_storage {
                     .map_err($crate::storage::AnyhowError::from)
             }
         }
+
+        impl<'tx> $crate::storage::MutableStorage for $name<'tx> {
+            fn insert(
+                &self,
+                hash: &stark_hash::Felt,
+                node: &$crate::Node,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .prepare_cached(concat!(
+                        "INSERT OR IGNORE INTO ",
+                        $table,
+                        " (hash, data) VALUES (?, ?)"
+                    ))
+                    .context(concat!("Preparing insert into ", $table))?
+                    .execute(rusqlite::params![hash.as_be_bytes(), node])
+                    .context(concat!("Inserting node into ", $table))?;
+
+                Ok(())
+            }
+        }
     };
 }
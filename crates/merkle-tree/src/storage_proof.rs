@@ -0,0 +1,219 @@
+//! Storage proofs which prove the value of a contract's storage slot relative to a
+//! [StorageCommitment], by combining the proofs of the
+//! [StorageCommitmentTree](crate::StorageCommitmentTree) and the contract's
+//! [ContractsStorageTree](crate::ContractsStorageTree).
+use crate::contract_state::calculate_contract_state_hash;
+use crate::{verify_proof, Membership, Node};
+use pathfinder_common::{
+    ClassHash, ContractAddress, ContractNonce, ContractRoot, StorageAddress, StorageCommitment,
+    StorageValue,
+};
+use stark_hash::Felt;
+
+/// Proves the value of a contract's storage slot, as generated by
+/// [StorageCommitmentTree::get_contract_state_hash_proof](crate::StorageCommitmentTree::get_contract_state_hash_proof).
+///
+/// The `contract_proof` leads from the storage commitment to the contract's state hash. This
+/// hash is recomputed from the [ContractData], whose `storage_proof` in turn leads from the
+/// contract's root to the storage slot, see [verify_contract_storage_proof].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractStorageProof {
+    pub contract_proof: Vec<Node>,
+    /// [None] if the contract is not deployed, in which case `contract_proof` proves its
+    /// absence.
+    pub contract_data: Option<ContractData>,
+}
+
+/// The state of a deployed contract, together with the proof of one of its storage slots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractData {
+    pub class_hash: ClassHash,
+    pub nonce: ContractNonce,
+    pub root: ContractRoot,
+    pub storage_proof: Vec<Node>,
+}
+
+/// Verifies a [ContractStorageProof] for the storage slot `key` of the contract at `address`
+/// in the state with the given `root`.
+///
+/// Returns
+/// - [Membership::Member] if the slot holds `value`,
+/// - [Membership::NonMember] if the slot is not set or the contract is not deployed, and
+/// - [None] if the proof is invalid, see [verify_proof].
+pub fn verify_contract_storage_proof(
+    root: StorageCommitment,
+    address: ContractAddress,
+    key: StorageAddress,
+    value: StorageValue,
+    proof: &ContractStorageProof,
+) -> Option<Membership> {
+    let data = match &proof.contract_data {
+        Some(data) => data,
+        None => {
+            let membership = verify_proof(
+                root.0,
+                address.view_bits(),
+                Felt::ZERO,
+                &proof.contract_proof,
+            )?;
+            return (membership == Membership::NonMember).then_some(Membership::NonMember);
+        }
+    };
+
+    let state_hash = calculate_contract_state_hash(data.class_hash, data.root, data.nonce);
+    let membership = verify_proof(
+        root.0,
+        address.view_bits(),
+        state_hash.0,
+        &proof.contract_proof,
+    )?;
+    if membership != Membership::Member {
+        return None;
+    }
+
+    verify_proof(data.root.0, key.view_bits(), value.0, &data.storage_proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::tests::open_database;
+    use crate::{ContractsStorageTree, StorageCommitmentTree};
+    use pathfinder_common::felt;
+    use pathfinder_storage::ContractsStateTable;
+
+    /// Deploys a contract at `0x1` with `0x5 = 0x6` in its storage, and one with a different
+    /// nonce at `0x11`.
+    fn setup(transaction: &rusqlite::Transaction<'_>) -> StorageCommitment {
+        let mut storage = ContractsStorageTree::load(transaction, ContractRoot::ZERO);
+        storage
+            .set(
+                StorageAddress::new_or_panic(felt!("0x5")),
+                StorageValue(felt!("0x6")),
+            )
+            .unwrap();
+        storage
+            .set(
+                StorageAddress::new_or_panic(felt!("0x15")),
+                StorageValue(felt!("0x16")),
+            )
+            .unwrap();
+        let contract_root = storage.commit_and_persist_changes().unwrap();
+
+        let mut tree = StorageCommitmentTree::load(transaction, StorageCommitment::ZERO);
+        for (address, nonce) in [(address(0x1), 0x8), (address(0x11), 0x9)] {
+            let class_hash = ClassHash(felt!("0x7"));
+            let nonce = ContractNonce(Felt::from_u64(nonce));
+            let state_hash = calculate_contract_state_hash(class_hash, contract_root, nonce);
+            ContractsStateTable::upsert(transaction, state_hash, class_hash, contract_root, nonce)
+                .unwrap();
+            tree.set(address, state_hash).unwrap();
+        }
+        tree.commit_and_persist_changes().unwrap()
+    }
+
+    fn address(value: u64) -> ContractAddress {
+        ContractAddress::new_or_panic(Felt::from_u64(value))
+    }
+
+    fn key(value: u64) -> StorageAddress {
+        StorageAddress::new_or_panic(Felt::from_u64(value))
+    }
+
+    #[test]
+    fn deployed_and_undeployed_contracts() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+        let tree = StorageCommitmentTree::load(&transaction, root);
+
+        let value = StorageValue(felt!("0x6"));
+        let proof = tree
+            .get_contract_state_hash_proof(address(0x1), key(0x5))
+            .unwrap();
+        let data = proof.contract_data.as_ref().unwrap();
+        assert_eq!(data.class_hash, ClassHash(felt!("0x7")));
+        assert_eq!(data.nonce, ContractNonce(felt!("0x8")));
+        assert_eq!(
+            verify_contract_storage_proof(root, address(0x1), key(0x5), value, &proof),
+            Some(Membership::Member)
+        );
+
+        let proof = tree
+            .get_contract_state_hash_proof(address(0x1), key(0x25))
+            .unwrap();
+        assert_eq!(
+            verify_contract_storage_proof(
+                root,
+                address(0x1),
+                key(0x25),
+                StorageValue::ZERO,
+                &proof
+            ),
+            Some(Membership::NonMember)
+        );
+
+        let proof = tree
+            .get_contract_state_hash_proof(address(0x2), key(0x5))
+            .unwrap();
+        assert_eq!(proof.contract_data, None);
+        assert_eq!(
+            verify_contract_storage_proof(root, address(0x2), key(0x5), value, &proof),
+            Some(Membership::NonMember)
+        );
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let mut conn = open_database();
+        let transaction = conn.transaction().unwrap();
+        let root = setup(&transaction);
+        let tree = StorageCommitmentTree::load(&transaction, root);
+
+        let value = StorageValue(felt!("0x6"));
+        let proof = tree
+            .get_contract_state_hash_proof(address(0x1), key(0x5))
+            .unwrap();
+
+        assert_eq!(
+            verify_contract_storage_proof(
+                root,
+                address(0x1),
+                key(0x5),
+                StorageValue(felt!("0x7")),
+                &proof
+            ),
+            None
+        );
+        // The proof is only valid for the contract and root it was generated for.
+        assert_eq!(
+            verify_contract_storage_proof(root, address(0x11), key(0x5), value, &proof),
+            None
+        );
+        assert_eq!(
+            verify_contract_storage_proof(
+                StorageCommitment(felt!("0x1234")),
+                address(0x1),
+                key(0x5),
+                value,
+                &proof
+            ),
+            None
+        );
+
+        let mut nonce = proof.clone();
+        nonce.contract_data.as_mut().unwrap().nonce = ContractNonce(felt!("0x9"));
+        assert_eq!(
+            verify_contract_storage_proof(root, address(0x1), key(0x5), value, &nonce),
+            None
+        );
+
+        // A deployed contract can't be passed off as undeployed.
+        let mut undeployed = proof;
+        undeployed.contract_data = None;
+        assert_eq!(
+            verify_contract_storage_proof(root, address(0x1), key(0x5), value, &undeployed),
+            None
+        );
+    }
+}
@@ -45,7 +45,7 @@
 //! The in-memory tree is built using a graph of `Rc<RefCell<Node>>` which is a bit painful.
 
 use crate::merkle_node::{BinaryNode, Direction, EdgeNode, InternalNode};
-use crate::storage::Storage;
+use crate::storage::{MutableStorage, Storage};
 use crate::Hash;
 use anyhow::Context;
 use bitvec::{prelude::BitSlice, prelude::BitVec, prelude::Msb0};
@@ -70,6 +70,15 @@ pub struct Update {
     pub added: HashMap<Felt, crate::Node>,
 }
 
+impl Update {
+    /// Inserts the [added](Update::added) nodes into the storage.
+    pub fn persist<S: MutableStorage>(&self, storage: &S) -> Result<(), S::Error> {
+        self.added
+            .iter()
+            .try_for_each(|(hash, node)| storage.insert(hash, node))
+    }
+}
+
 impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
     pub fn new(root: Felt) -> Self {
         let root_node = Rc::new(RefCell::new(InternalNode::Unresolved(root)));
@@ -98,6 +107,37 @@ impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
         Ok(Update { root, added })
     }
 
+    /// Returns the root the tree would have if it were committed now.
+    ///
+    /// Unlike [commit](MerkleTree::commit), the hashes of mutated nodes are not kept. The tree
+    /// is left as is, so it can still be mutated or discarded, and committing it later results
+    /// in the same root and [added](Update::added) nodes.
+    pub fn root_without_commit(&self) -> Felt {
+        Self::subtree_hash(&self.root.borrow())
+    }
+
+    /// Calculates the hash of the subtree, including any uncommitted changes.
+    fn subtree_hash(node: &InternalNode) -> Felt {
+        use InternalNode::*;
+        match node {
+            Unresolved(hash) | Leaf(hash) => *hash,
+            Binary(binary) => binary.hash.unwrap_or_else(|| {
+                crate::Node::Binary {
+                    left: Self::subtree_hash(&binary.left.borrow()),
+                    right: Self::subtree_hash(&binary.right.borrow()),
+                }
+                .hash::<H>()
+            }),
+            Edge(edge) => edge.hash.unwrap_or_else(|| {
+                crate::Node::Edge {
+                    child: Self::subtree_hash(&edge.child.borrow()),
+                    path: edge.path.clone(),
+                }
+                .hash::<H>()
+            }),
+        }
+    }
+
     /// Persists any changes in this subtree to storage.
     ///
     /// This necessitates recursively calculating the hash of, and
@@ -148,7 +188,8 @@ impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
         Ok(())
     }
 
-    /// Sets the value of a key. To delete a key, set the value to [Felt::ZERO].
+    /// Sets the value of a key. Setting the value to [Felt::ZERO] is the same as
+    /// [deleting](MerkleTree::delete) the key.
     pub fn set(
         &mut self,
         storage: &impl Storage,
@@ -156,7 +197,7 @@ impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
         value: Felt,
     ) -> anyhow::Result<()> {
         if value == Felt::ZERO {
-            return self.delete_leaf(storage, key);
+            return self.delete(storage, key);
         }
 
         // Changing or inserting a new leaf into the tree will change the hashes
@@ -282,11 +323,249 @@ impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
         Ok(())
     }
 
-    /// Deletes a leaf node from the tree.
+    /// Sets the values of multiple keys, with the same result as calling [MerkleTree::set]
+    /// for each entry in order, i.e. the last write to a key wins.
+    ///
+    /// The tree is updated in a single descent: the entries are sorted by key and split
+    /// between the children of each binary node, and wherever they diverge from the path of
+    /// an edge node. Each affected node is therefore loaded from storage and rebuilt only
+    /// once, no matter how many of the keys below it are set.
+    pub fn set_batch(
+        &mut self,
+        storage: &impl Storage,
+        entries: &[(BitVec<Msb0, u8>, Felt)],
+    ) -> anyhow::Result<()> {
+        // The sort is stable, so writes to the same key remain in their original order.
+        let mut sorted = entries.iter().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut last_writes: Vec<&(BitVec<Msb0, u8>, Felt)> = Vec::with_capacity(sorted.len());
+        for entry in sorted {
+            match last_writes.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => last_writes.push(entry),
+            }
+        }
+
+        let root = match self.root.borrow().is_empty() {
+            true => None,
+            false => Some(self.root.clone()),
+        };
+
+        self.root = match self.update_subtree(storage, root, 0, &last_writes)? {
+            Some(root) => root,
+            None => Rc::new(RefCell::new(InternalNode::Unresolved(Felt::ZERO))),
+        };
+
+        Ok(())
+    }
+
+    /// Applies the sorted and unique `entries` to the subtree at `height`, all of whose keys
+    /// lie below it. [None] represents an empty subtree.
+    ///
+    /// Returns the updated subtree. Its nodes are new, and therefore dirty, wherever they
+    /// changed, while unchanged subtrees are shared with the original.
+    fn update_subtree(
+        &self,
+        storage: &impl Storage,
+        node: Option<Rc<RefCell<InternalNode>>>,
+        height: usize,
+        entries: &[&(BitVec<Msb0, u8>, Felt)],
+    ) -> anyhow::Result<Option<Rc<RefCell<InternalNode>>>> {
+        let node = match (node, entries.is_empty()) {
+            (node, true) => return Ok(node),
+            (None, false) => return self.build_subtree(storage, height, entries),
+            (Some(node), false) => node,
+        };
+
+        let resolved = match &*node.borrow() {
+            InternalNode::Unresolved(hash) => self.resolve(storage, *hash, height)?,
+            other => other.clone(),
+        };
+
+        use InternalNode::*;
+        match resolved {
+            // The key is unique, so this is its only entry.
+            Leaf(_) => Ok(Self::new_leaf(entries[0].1)),
+            Binary(binary) => {
+                let split = entries.partition_point(|(key, _)| !key[height]);
+                let left =
+                    self.update_subtree(storage, Some(binary.left), height + 1, &entries[..split])?;
+                let right = self.update_subtree(
+                    storage,
+                    Some(binary.right),
+                    height + 1,
+                    &entries[split..],
+                )?;
+
+                self.join(storage, height, left, right)
+            }
+            Edge(edge) => {
+                // The keys are sorted, so the first and last one diverge the earliest.
+                let common = edge
+                    .common_path(&entries[0].0)
+                    .len()
+                    .min(edge.common_path(&entries[entries.len() - 1].0).len());
+
+                if common == edge.path.len() {
+                    let child_height = height + edge.path.len();
+                    return match self.update_subtree(
+                        storage,
+                        Some(edge.child),
+                        child_height,
+                        entries,
+                    )? {
+                        Some(child) => self
+                            .with_prefix(storage, height, edge.path, child)
+                            .map(Some),
+                        None => Ok(None),
+                    };
+                }
+
+                // Some keys leave the edge's path at `branch_height`, so the edge is split into
+                // a binary node with the existing child below one side, and the empty other side.
+                let branch_height = height + common;
+                let child_path = edge.path[common + 1..].to_bitvec();
+                let existing = match child_path.is_empty() {
+                    true => edge.child,
+                    false => Rc::new(RefCell::new(Edge(EdgeNode {
+                        hash: None,
+                        height: branch_height + 1,
+                        path: child_path,
+                        child: edge.child,
+                    }))),
+                };
+                let (left, right) = match Direction::from(edge.path[common]) {
+                    Direction::Left => (Some(existing), None),
+                    Direction::Right => (None, Some(existing)),
+                };
+
+                let split = entries.partition_point(|(key, _)| !key[branch_height]);
+                let left =
+                    self.update_subtree(storage, left, branch_height + 1, &entries[..split])?;
+                let right =
+                    self.update_subtree(storage, right, branch_height + 1, &entries[split..])?;
+
+                match self.join(storage, branch_height, left, right)? {
+                    Some(branch) => {
+                        let prefix = edge.path[..common].to_bitvec();
+                        self.with_prefix(storage, height, prefix, branch).map(Some)
+                    }
+                    None => Ok(None),
+                }
+            }
+            Unresolved(_) => unreachable!("The node was resolved above"),
+        }
+    }
+
+    /// Builds a new subtree at `height` from the sorted and unique `entries`.
+    fn build_subtree(
+        &self,
+        storage: &impl Storage,
+        height: usize,
+        entries: &[&(BitVec<Msb0, u8>, Felt)],
+    ) -> anyhow::Result<Option<Rc<RefCell<InternalNode>>>> {
+        let (first, last) = match entries {
+            [] => return Ok(None),
+            [(key, value)] => {
+                return match Self::new_leaf(*value) {
+                    Some(leaf) => self
+                        .with_prefix(storage, height, key[height..].to_bitvec(), leaf)
+                        .map(Some),
+                    None => Ok(None),
+                }
+            }
+            [first, .., last] => (&first.0, &last.0),
+        };
+
+        // The keys are sorted, so the first and last one diverge the earliest.
+        let common = first[height..]
+            .iter()
+            .zip(last[height..].iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let branch_height = height + common;
+
+        let split = entries.partition_point(|(key, _)| !key[branch_height]);
+        let left = self.build_subtree(storage, branch_height + 1, &entries[..split])?;
+        let right = self.build_subtree(storage, branch_height + 1, &entries[split..])?;
+
+        match self.join(storage, branch_height, left, right)? {
+            Some(branch) => {
+                let prefix = first[height..branch_height].to_bitvec();
+                self.with_prefix(storage, height, prefix, branch).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// A leaf holding `value`, or [None] if the value is [Felt::ZERO], i.e. deleted.
+    fn new_leaf(value: Felt) -> Option<Rc<RefCell<InternalNode>>> {
+        match value == Felt::ZERO {
+            true => None,
+            false => Some(Rc::new(RefCell::new(InternalNode::Leaf(value)))),
+        }
+    }
+
+    /// Joins the children of the binary node at `height`. If one of them is empty, the other
+    /// is instead attached to an edge node.
+    fn join(
+        &self,
+        storage: &impl Storage,
+        height: usize,
+        left: Option<Rc<RefCell<InternalNode>>>,
+        right: Option<Rc<RefCell<InternalNode>>>,
+    ) -> anyhow::Result<Option<Rc<RefCell<InternalNode>>>> {
+        let (direction, child) = match (left, right) {
+            (Some(left), Some(right)) => {
+                let binary = InternalNode::Binary(BinaryNode {
+                    hash: None,
+                    height,
+                    left,
+                    right,
+                });
+                return Ok(Some(Rc::new(RefCell::new(binary))));
+            }
+            (Some(left), None) => (Direction::Left, left),
+            (None, Some(right)) => (Direction::Right, right),
+            (None, None) => return Ok(None),
+        };
+
+        let path = std::iter::once(bool::from(direction)).collect::<BitVec<_, _>>();
+        self.with_prefix(storage, height, path, child).map(Some)
+    }
+
+    /// Attaches `child` to a new edge node at `height` with the given `path`.
     ///
-    /// This is not an external facing API; the functionality is instead accessed by calling
-    /// [`MerkleTree::set`] with value set to [`Felt::ZERO`].
-    fn delete_leaf(
+    /// The edge is merged with `child` if that is also an edge, see [MerkleTree::merge_edges].
+    /// An empty `path` returns `child` itself.
+    fn with_prefix(
+        &self,
+        storage: &impl Storage,
+        height: usize,
+        path: BitVec<Msb0, u8>,
+        child: Rc<RefCell<InternalNode>>,
+    ) -> anyhow::Result<Rc<RefCell<InternalNode>>> {
+        if path.is_empty() {
+            return Ok(child);
+        }
+
+        let mut edge = EdgeNode {
+            hash: None,
+            height,
+            path,
+            child,
+        };
+        self.merge_edges(storage, &mut edge)?;
+
+        Ok(Rc::new(RefCell::new(InternalNode::Edge(edge))))
+    }
+
+    /// Deletes the leaf at `key` from the tree, if it exists.
+    ///
+    /// Nodes which become redundant are collapsed, so that the resulting tree is identical
+    /// to one in which the key was never set.
+    pub fn delete(
         &mut self,
         storage: &impl Storage,
         key: &BitSlice<Msb0, u8>,
@@ -573,88 +852,240 @@ impl<H: Hash, const HEIGHT: usize> MerkleTree<H, HEIGHT> {
     where
         VisitorFn: FnMut(&InternalNode, &BitSlice<Msb0, u8>) -> ControlFlow<X, Visit>,
     {
-        use bitvec::prelude::bitvec;
+        let mut visiting = vec![VisitedNode::root(self)];
+
+        while let Some(VisitedNode { node, path }) = visiting.pop() {
+            let current_node = &*node.borrow();
+            if !matches!(current_node, InternalNode::Unresolved(Felt::ZERO)) {
+                match visitor_fn(current_node, &path) {
+                    ControlFlow::Continue(Visit::ContinueDeeper) => {
+                        // the default, no action, just continue deeper
+                    }
+                    ControlFlow::Continue(Visit::StopSubtree) => {
+                        // make sure we don't add any more to `visiting` on this subtree
+                        continue;
+                    }
+                    ControlFlow::Break(x) => {
+                        // early exit
+                        return Ok(Some(x));
+                    }
+                }
+            }
+            self.visit_children(storage, current_node, path, &mut visiting)?;
+        }
 
-        #[allow(dead_code)]
-        struct VisitedNode {
-            node: Rc<RefCell<InternalNode>>,
-            path: BitVec<Msb0, u8>,
+        Ok(None)
+    }
+
+    /// Iterates over all leaves of the tree as `(key, value)` pairs, in ascending key order.
+    ///
+    /// Nodes are loaded from storage as the iteration reaches them and are not kept in memory
+    /// afterwards, i.e. this only holds the nodes along the current path and their siblings.
+    pub fn leaves<'a, S: Storage>(
+        &'a self,
+        storage: &'a S,
+    ) -> impl Iterator<Item = anyhow::Result<(BitVec<Msb0, u8>, Felt)>> + 'a {
+        let mut visiting = vec![VisitedNode::root(self)];
+
+        std::iter::from_fn(move || loop {
+            let VisitedNode { node, path } = visiting.pop()?;
+            let current_node = &*node.borrow();
+
+            if let InternalNode::Leaf(value) = current_node {
+                return Some(Ok((path, *value)));
+            }
+
+            if let Err(e) = self.visit_children(storage, current_node, path, &mut visiting) {
+                // Stop the iteration, the remaining nodes can't be trusted to be complete.
+                visiting.clear();
+                return Some(Err(e));
+            }
+        })
+    }
+
+    /// Pushes the children of `node` onto the `visiting` stack of a depth-first traversal such
+    /// that the left child is visited first. An unresolved node is replaced by the resolved node.
+    fn visit_children(
+        &self,
+        storage: &impl Storage,
+        node: &InternalNode,
+        path: BitVec<Msb0, u8>,
+        visiting: &mut Vec<VisitedNode>,
+    ) -> anyhow::Result<()> {
+        match node {
+            InternalNode::Binary(b) => {
+                visiting.push(VisitedNode {
+                    node: b.right.clone(),
+                    path: {
+                        let mut path_right = path.clone();
+                        path_right.push(Direction::Right.into());
+                        path_right
+                    },
+                });
+                visiting.push(VisitedNode {
+                    node: b.left.clone(),
+                    path: {
+                        let mut path_left = path;
+                        path_left.push(Direction::Left.into());
+                        path_left
+                    },
+                });
+            }
+            InternalNode::Edge(e) => {
+                visiting.push(VisitedNode {
+                    node: e.child.clone(),
+                    path: {
+                        let mut extended_path = path;
+                        extended_path.extend_from_bitslice(&e.path);
+                        extended_path
+                    },
+                });
+            }
+            InternalNode::Leaf(_) => {}
+            InternalNode::Unresolved(hash) => {
+                // Zero means empty tree, so nothing to resolve
+                if hash != &Felt::ZERO {
+                    visiting.push(VisitedNode {
+                        node: Rc::new(RefCell::new(self.resolve(storage, *hash, path.len())?)),
+                        path,
+                    });
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Returns the keys whose values differ between the trees rooted at `root_a` and `root_b`.
+    ///
+    /// Both trees are traversed simultaneously. Subtrees which have the same hash in both trees
+    /// are identical and are therefore skipped without being loaded from storage. Keys which are
+    /// only present in one of the trees are also included.
+    ///
+    /// The keys are returned in ascending order.
+    pub fn diff_roots(
+        storage: &impl Storage,
+        root_a: Felt,
+        root_b: Felt,
+    ) -> anyhow::Result<Vec<BitVec<Msb0, u8>>> {
+        /// A subtree of one of the trees being compared.
+        #[derive(Clone, PartialEq)]
+        enum Subtree {
+            /// There is no subtree here.
+            Empty,
+            /// A node (or leaf value) known only by its hash.
+            Unresolved(Felt),
+            /// A binary node with its children's hashes.
+            Binary { left: Felt, right: Felt },
+            /// An edge node, or the remainder of one after some of its path has been consumed.
+            Edge { path: BitVec<Msb0, u8>, child: Felt },
         }
 
-        let mut visiting = vec![VisitedNode {
-            node: self.root.clone(),
-            path: bitvec![Msb0, u8;],
-        }];
+        fn from_root(root: Felt) -> Subtree {
+            match root {
+                Felt::ZERO => Subtree::Empty,
+                root => Subtree::Unresolved(root),
+            }
+        }
 
-        loop {
-            match visiting.pop() {
-                None => break,
-                Some(VisitedNode { node, path }) => {
-                    let current_node = &*node.borrow();
-                    if !matches!(current_node, InternalNode::Unresolved(Felt::ZERO)) {
-                        match visitor_fn(current_node, &path) {
-                            ControlFlow::Continue(Visit::ContinueDeeper) => {
-                                // the default, no action, just continue deeper
-                            }
-                            ControlFlow::Continue(Visit::StopSubtree) => {
-                                // make sure we don't add any more to `visiting` on this subtree
-                                continue;
-                            }
-                            ControlFlow::Break(x) => {
-                                // early exit
-                                return Ok(Some(x));
-                            }
-                        }
-                    }
-                    match current_node {
-                        InternalNode::Binary(b) => {
-                            visiting.push(VisitedNode {
-                                node: b.right.clone(),
-                                path: {
-                                    let mut path_right = path.clone();
-                                    path_right.push(Direction::Right.into());
-                                    path_right
-                                },
-                            });
-                            visiting.push(VisitedNode {
-                                node: b.left.clone(),
-                                path: {
-                                    let mut path_left = path.clone();
-                                    path_left.push(Direction::Left.into());
-                                    path_left
-                                },
-                            });
-                        }
-                        InternalNode::Edge(e) => {
-                            visiting.push(VisitedNode {
-                                node: e.child.clone(),
-                                path: {
-                                    let mut extended_path = path.clone();
-                                    extended_path.extend_from_bitslice(&e.path);
-                                    extended_path
-                                },
-                            });
-                        }
-                        InternalNode::Leaf(_) => {}
-                        InternalNode::Unresolved(hash) => {
-                            // Zero means empty tree, so nothing to resolve
-                            if hash != &Felt::ZERO {
-                                visiting.push(VisitedNode {
-                                    node: Rc::new(RefCell::new(self.resolve(
-                                        storage,
-                                        *hash,
-                                        path.len(),
-                                    )?)),
-                                    path,
-                                });
-                            }
-                        }
+        fn resolve(storage: &impl Storage, subtree: Subtree) -> anyhow::Result<Subtree> {
+            match subtree {
+                Subtree::Unresolved(hash) => {
+                    let node = storage
+                        .get(&hash)?
+                        .with_context(|| format!("Node does not exist: {hash}"))?;
+                    Ok(match node {
+                        crate::Node::Binary { left, right } => Subtree::Binary { left, right },
+                        crate::Node::Edge { child, path } => Subtree::Edge { path, child },
+                    })
+                }
+                other => Ok(other),
+            }
+        }
+
+        /// Splits a resolved subtree into its left and right children.
+        fn children(subtree: Subtree) -> (Subtree, Subtree) {
+            match subtree {
+                Subtree::Empty => (Subtree::Empty, Subtree::Empty),
+                Subtree::Binary { left, right } => {
+                    (Subtree::Unresolved(left), Subtree::Unresolved(right))
+                }
+                Subtree::Edge { path, child } => {
+                    let remainder = match path.len() {
+                        1 => Subtree::Unresolved(child),
+                        _ => Subtree::Edge {
+                            path: path[1..].to_bitvec(),
+                            child,
+                        },
                     };
+                    match Direction::from(path[0]) {
+                        Direction::Left => (remainder, Subtree::Empty),
+                        Direction::Right => (Subtree::Empty, remainder),
+                    }
                 }
+                Subtree::Unresolved(_) => unreachable!("Subtree must be resolved"),
             }
         }
 
-        Ok(None)
+        fn diff(
+            storage: &impl Storage,
+            a: Subtree,
+            b: Subtree,
+            height: usize,
+            path: &mut BitVec<Msb0, u8>,
+            differences: &mut Vec<BitVec<Msb0, u8>>,
+        ) -> anyhow::Result<()> {
+            // Identical subtrees contain identical leaves.
+            if a == b {
+                return Ok(());
+            }
+
+            // At full height the subtrees are leaf values, which we already know differ.
+            if path.len() == height {
+                differences.push(path.clone());
+                return Ok(());
+            }
+
+            let (a_left, a_right) = children(resolve(storage, a)?);
+            let (b_left, b_right) = children(resolve(storage, b)?);
+
+            path.push(Direction::Left.into());
+            diff(storage, a_left, b_left, height, path, differences)?;
+            path.pop();
+
+            path.push(Direction::Right.into());
+            diff(storage, a_right, b_right, height, path, differences)?;
+            path.pop();
+
+            Ok(())
+        }
+
+        let mut differences = Vec::new();
+        diff(
+            storage,
+            from_root(root_a),
+            from_root(root_b),
+            HEIGHT,
+            &mut BitVec::with_capacity(HEIGHT),
+            &mut differences,
+        )?;
+
+        Ok(differences)
+    }
+}
+
+/// A node on the stack of a depth-first traversal, together with the path leading to it.
+struct VisitedNode {
+    node: Rc<RefCell<InternalNode>>,
+    path: BitVec<Msb0, u8>,
+}
+
+impl VisitedNode {
+    fn root<H: Hash, const HEIGHT: usize>(tree: &MerkleTree<H, HEIGHT>) -> Self {
+        Self {
+            node: tree.root.clone(),
+            path: BitVec::new(),
+        }
     }
 }
 
@@ -700,13 +1131,114 @@ mod tests {
     ) -> Felt {
         let update = tree.commit().unwrap();
 
-        for (hash, node) in update.added {
-            storage.insert(&hash, &node).unwrap();
-        }
+        storage.insert_batch(&update.added).unwrap();
 
         update.root
     }
 
+    #[test]
+    fn persist_more_nodes_than_a_batch() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let storage = TestStorage::create(&transaction);
+
+        let mut uut = TestTree::empty();
+        let values = (1..=1000u64)
+            .map(|i| (Felt::from_u64(i), Felt::from_u64(i + 1)))
+            .collect::<Vec<_>>();
+        for (key, value) in &values {
+            uut.set(&storage, key.view_bits(), *value).unwrap();
+        }
+
+        let update = uut.commit().unwrap();
+        assert!(update.added.len() > crate::storage::INSERT_BATCH_SIZE);
+        storage.insert_batch(&update.added).unwrap();
+
+        let uut = TestTree::new(update.root);
+        for (key, value) in values {
+            assert_eq!(uut.get(&storage, key.view_bits()).unwrap(), Some(value));
+        }
+    }
+
+    #[test]
+    fn root_without_commit() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        let transaction = conn.transaction().unwrap();
+        let storage = TestStorage::create(&transaction);
+
+        let mut uut = TestTree::empty();
+        assert_eq!(uut.root_without_commit(), Felt::ZERO);
+
+        let values = (1..=100u64)
+            .map(|i| (Felt::from_u64(i * 7), Felt::from_u64(i)))
+            .collect::<Vec<_>>();
+        for (key, value) in &values[..50] {
+            uut.set(&storage, key.view_bits(), *value).unwrap();
+        }
+        let speculative = uut.root_without_commit();
+        assert_eq!(uut.root_without_commit(), speculative);
+
+        // Build on top of a committed tree to also cover nodes which are already hashed.
+        let root = commit_and_persist(uut, &storage);
+        assert_eq!(root, speculative);
+
+        let mut uut = TestTree::new(root);
+        assert_eq!(uut.root_without_commit(), root);
+        for (key, value) in &values[50..] {
+            uut.set(&storage, key.view_bits(), *value).unwrap();
+        }
+        let speculative = uut.root_without_commit();
+        assert_ne!(speculative, root);
+
+        let update = uut.commit().unwrap();
+        assert_eq!(update.root, speculative);
+        update.persist(&storage).unwrap();
+
+        let uut = TestTree::new(update.root);
+        for (key, value) in values {
+            assert_eq!(uut.get(&storage, key.view_bits()).unwrap(), Some(value));
+        }
+    }
+
+    #[test]
+    fn custom_storage() {
+        /// Storage which keeps the nodes in memory.
+        #[derive(Default)]
+        struct MemoryStorage(RefCell<HashMap<Felt, crate::Node>>);
+
+        impl Storage for MemoryStorage {
+            type Error = std::convert::Infallible;
+
+            fn get(&self, node: &Felt) -> Result<Option<crate::Node>, Self::Error> {
+                Ok(self.0.borrow().get(node).cloned())
+            }
+        }
+
+        impl MutableStorage for MemoryStorage {
+            fn insert(&self, hash: &Felt, node: &crate::Node) -> Result<(), Self::Error> {
+                self.0.borrow_mut().insert(*hash, node.clone());
+                Ok(())
+            }
+        }
+
+        let storage = MemoryStorage::default();
+        let mut uut = TestTree::empty();
+        let values = (1..=100u64)
+            .map(|i| (Felt::from_u64(i), Felt::from_u64(i + 1)))
+            .collect::<Vec<_>>();
+        for (key, value) in &values {
+            uut.set(&storage, key.view_bits(), *value).unwrap();
+        }
+
+        let update = uut.commit().unwrap();
+        update.persist(&storage).unwrap();
+
+        let uut = TestTree::new(update.root);
+        for (key, value) in values {
+            assert_eq!(uut.get(&storage, key.view_bits()).unwrap(), Some(value));
+        }
+    }
+
     #[test]
     fn get_empty() {
         let mut conn = rusqlite::Connection::open_in_memory().unwrap();
@@ -718,6 +1250,83 @@ mod tests {
         assert_eq!(uut.get(&storage, &key).unwrap(), None);
     }
 
+    mod set_batch {
+        use super::*;
+        use rand::{Rng, SeedableRng};
+
+        #[test]
+        fn matches_sequential_set() {
+            // Included in the assertion messages to reproduce a failing run.
+            let seed = rand::random();
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+            for _ in 0..20 {
+                let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+                let transaction = conn.transaction().unwrap();
+                let storage = TestStorage::create(&transaction);
+
+                // A small pool of keys causes overwrites within a batch, and zero values
+                // cause deletes. Small keys share long paths, random ones split near the root.
+                let keys = (0..rng.gen_range(1..50))
+                    .map(|_| {
+                        let key = match rng.gen_bool(0.5) {
+                            true => Felt::from_u64(rng.gen_range(0..64)),
+                            false => {
+                                let mut bytes: [u8; 32] = rng.gen();
+                                bytes[0] &= 0x03;
+                                Felt::from_be_bytes(bytes).unwrap()
+                            }
+                        };
+                        key.view_bits().to_bitvec()
+                    })
+                    .collect::<Vec<_>>();
+                let mut random_entries = |count: usize| {
+                    (0..count)
+                        .map(|_| {
+                            let key = keys[rng.gen_range(0..keys.len())].clone();
+                            let value = match rng.gen_bool(0.2) {
+                                true => Felt::ZERO,
+                                false => Felt::from_u64(rng.gen()),
+                            };
+                            (key, value)
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                // Start from a persisted tree so that the batch also has to resolve nodes.
+                let mut initial = TestTree::empty();
+                for (key, value) in random_entries(30) {
+                    initial.set(&storage, &key, value).unwrap();
+                }
+                let root = commit_and_persist(initial, &storage);
+
+                let entries = random_entries(100);
+
+                let mut sequential = TestTree::new(root);
+                for (key, value) in &entries {
+                    sequential.set(&storage, key, *value).unwrap();
+                }
+
+                let mut batched = TestTree::new(root);
+                batched.set_batch(&storage, &entries).unwrap();
+
+                let expected = sequential.clone();
+                let root = commit_and_persist(batched, &storage);
+                assert_eq!(root, sequential.commit().unwrap().root, "Seed: {seed}");
+
+                // All of the new nodes were persisted.
+                let batched = TestTree::new(root);
+                for key in &keys {
+                    assert_eq!(
+                        batched.get(&storage, key).unwrap(),
+                        expected.get(&storage, key).unwrap(),
+                        "Seed: {seed}"
+                    );
+                }
+            }
+        }
+    }
+
     mod set {
         use super::*;
 
@@ -967,7 +1576,7 @@ mod tests {
         }
     }
 
-    mod delete_leaf {
+    mod delete {
         use super::*;
 
         #[test]
@@ -978,7 +1587,7 @@ mod tests {
             let storage = TestStorage::create(&transaction);
 
             let key = felt!("0x123abc").view_bits().to_bitvec();
-            uut.delete_leaf(&storage, &key).unwrap();
+            uut.delete(&storage, &key).unwrap();
 
             assert_eq!(*uut.root.borrow(), InternalNode::Unresolved(Felt::ZERO));
         }
@@ -994,7 +1603,7 @@ mod tests {
             let value = felt!("0xabc");
 
             uut.set(&storage, &key, value).unwrap();
-            uut.delete_leaf(&storage, &key).unwrap();
+            uut.delete(&storage, &key).unwrap();
 
             assert_eq!(uut.get(&storage, &key).unwrap(), None);
             assert_eq!(*uut.root.borrow(), InternalNode::Unresolved(Felt::ZERO));
@@ -1019,12 +1628,46 @@ mod tests {
             uut.set(&storage, &key1, val1).unwrap();
             uut.set(&storage, &key2, val2).unwrap();
 
-            uut.delete_leaf(&storage, &key1).unwrap();
+            uut.delete(&storage, &key1).unwrap();
 
             assert_eq!(uut.get(&storage, &key0).unwrap(), Some(val0));
             assert_eq!(uut.get(&storage, &key1).unwrap(), None);
             assert_eq!(uut.get(&storage, &key2).unwrap(), Some(val2));
         }
+
+        #[test]
+        fn remaining_sibling_is_merged_into_parent_edge() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let storage = TestStorage::create(&transaction);
+
+            // The root is an edge leading to a binary node whose children are an edge to
+            // key0's leaf, and an edge to the binary node holding key1 and key2. Deleting
+            // key0 turns the first binary node into an edge, which must be merged with both
+            // the root edge and the remaining sibling edge.
+            let key0 = felt!("0x10").view_bits().to_bitvec();
+            let key1 = felt!("0x18").view_bits().to_bitvec();
+            let key2 = felt!("0x19").view_bits().to_bitvec();
+
+            let mut expected = TestTree::empty();
+            expected.set(&storage, &key1, felt!("0x2")).unwrap();
+            expected.set(&storage, &key2, felt!("0x3")).unwrap();
+            let expected = commit_and_persist(expected, &storage);
+
+            let mut uut = TestTree::empty();
+            uut.set(&storage, &key0, felt!("0x1")).unwrap();
+            uut.set(&storage, &key1, felt!("0x2")).unwrap();
+            uut.set(&storage, &key2, felt!("0x3")).unwrap();
+            let root = commit_and_persist(uut, &storage);
+
+            // Delete from a tree loaded from storage, so that nodes are resolved on the way.
+            let mut uut = TestTree::new(root);
+            uut.delete(&storage, &key0).unwrap();
+            assert_eq!(uut.get(&storage, &key0).unwrap(), None);
+
+            let root = commit_and_persist(uut, &storage);
+            assert_eq!(root, expected);
+        }
     }
 
     mod persistence {
@@ -1506,103 +2149,101 @@ mod tests {
         }
     }
 
-    mod proofs {
-        use crate::storage::Storage;
-        use crate::tree::tests::commit_and_persist;
-        use crate::PedersenHash;
+    mod diff_roots {
+        use super::*;
 
-        use super::{Direction, MerkleTree, TestStorage, TestTree};
-        use bitvec::prelude::Msb0;
-        use bitvec::slice::BitSlice;
-        use pathfinder_common::felt;
-        use rusqlite::Transaction;
-        use stark_hash::Felt;
+        #[test]
+        fn identical_roots() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let mut uut = TestTree::empty();
+            let storage = TestStorage::create(&transaction);
 
-        #[derive(Debug, PartialEq, Eq)]
-        pub enum Membership {
-            Member,
-            NonMember,
-        }
-
-        /// Verifies that the key `key` with value `value` is indeed part of the MPT that has root
-        /// `root`, given `proofs`.
-        /// Supports proofs of non-membership as well as proof of membership: this function returns
-        /// an enum corresponding to the membership of `value`, or returns `None` in case of a hash mismatch.
-        /// The algorithm follows this logic:
-        /// 1. init expected_hash <- root hash
-        /// 2. loop over nodes: current <- nodes[i]
-        ///    1. verify the current node's hash matches expected_hash (if not then we have a bad proof)
-        ///    2. move towards the target - if current is:
-        ///       1. binary node then choose the child that moves towards the target, else if
-        ///       2. edge node then check the path against the target bits
-        ///          1. If it matches then proceed with the child, else
-        ///          2. if it does not match then we now have a proof that the target does not exist
-        ///    3. nibble off target bits according to which child you got in (2). If all bits are gone then you
-        ///       have reached the target and the child hash is the value you wanted and the proof is complete.
-        ///    4. set expected_hash <- to the child hash
-        /// 3. check that the expected_hash is `value` (we should've reached the leaf)
-        fn verify_proof(
-            root: Felt,
-            key: &BitSlice<Msb0, u8>,
-            value: Felt,
-            proofs: &[crate::Node],
-        ) -> Option<Membership> {
-            // Protect from ill-formed keys
-            if key.len() != 251 {
-                return None;
-            }
+            uut.set(&storage, felt!("0x1").view_bits(), felt!("0xa"))
+                .unwrap();
+            uut.set(&storage, felt!("0x2").view_bits(), felt!("0xb"))
+                .unwrap();
+            let root = commit_and_persist(uut, &storage);
 
-            let mut expected_hash = root;
-            let mut remaining_path: &BitSlice<Msb0, u8> = key;
+            let diff = TestTree::diff_roots(&storage, root, root).unwrap();
+            assert!(diff.is_empty());
+        }
 
-            for proof_node in proofs.iter() {
-                // Hash mismatch? Return None.
-                if proof_node.hash::<PedersenHash>() != expected_hash {
-                    return None;
-                }
-                match proof_node {
-                    crate::Node::Binary { left, right } => {
-                        // Direction will always correspond to the 0th index
-                        // because we're removing bits on every iteration.
-                        let direction = Direction::from(remaining_path[0]);
-
-                        // Set the next hash to be the left or right hash,
-                        // depending on the direction
-                        expected_hash = match direction {
-                            Direction::Left => *left,
-                            Direction::Right => *right,
-                        };
+        #[test]
+        fn against_empty_tree() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let mut uut = TestTree::empty();
+            let storage = TestStorage::create(&transaction);
 
-                        // Advance by a single bit
-                        remaining_path = &remaining_path[1..];
-                    }
-                    crate::Node::Edge { child, path } => {
-                        if path != &remaining_path[..path.len()] {
-                            // If paths don't match, we've found a proof of non membership because we:
-                            // 1. Correctly moved towards the target insofar as is possible, and
-                            // 2. hashing all the nodes along the path does result in the root hash, which means
-                            // 3. the target definitely does not exist in this tree
-                            return Some(Membership::NonMember);
-                        }
+            let key0 = felt!("0x1");
+            let key1 = felt!("0x5");
+            uut.set(&storage, key0.view_bits(), felt!("0xa")).unwrap();
+            uut.set(&storage, key1.view_bits(), felt!("0xb")).unwrap();
+            let root = commit_and_persist(uut, &storage);
 
-                        // Set the next hash to the child's hash
-                        expected_hash = *child;
+            let expected = vec![key0.view_bits().to_bitvec(), key1.view_bits().to_bitvec()];
 
-                        // Advance by the whole edge path
-                        remaining_path = &remaining_path[path.len()..];
-                    }
-                }
-            }
+            let diff = TestTree::diff_roots(&storage, root, Felt::ZERO).unwrap();
+            assert_eq!(diff, expected);
 
-            // At this point, we should reach `value` !
-            if expected_hash == value {
-                Some(Membership::Member)
-            } else {
-                // Hash mismatch. Return `None`.
-                None
-            }
+            let diff = TestTree::diff_roots(&storage, Felt::ZERO, root).unwrap();
+            assert_eq!(diff, expected);
         }
 
+        #[test]
+        fn modified_added_and_removed() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let mut uut = TestTree::empty();
+            let storage = TestStorage::create(&transaction);
+
+            let unchanged = felt!("0x99cadc82");
+            let modified = felt!("0x901823");
+            let removed = felt!("0x8975");
+            let added = felt!("0x8976");
+
+            uut.set(&storage, unchanged.view_bits(), felt!("0x1"))
+                .unwrap();
+            uut.set(&storage, modified.view_bits(), felt!("0x2"))
+                .unwrap();
+            uut.set(&storage, removed.view_bits(), felt!("0x3"))
+                .unwrap();
+            let root_a = commit_and_persist(uut, &storage);
+
+            let mut uut = TestTree::new(root_a);
+            uut.set(&storage, modified.view_bits(), felt!("0x4"))
+                .unwrap();
+            uut.set(&storage, removed.view_bits(), Felt::ZERO).unwrap();
+            uut.set(&storage, added.view_bits(), felt!("0x5")).unwrap();
+            let root_b = commit_and_persist(uut, &storage);
+
+            let expected = vec![
+                removed.view_bits().to_bitvec(),
+                added.view_bits().to_bitvec(),
+                modified.view_bits().to_bitvec(),
+            ];
+
+            let diff = TestTree::diff_roots(&storage, root_a, root_b).unwrap();
+            assert_eq!(diff, expected);
+
+            let diff = TestTree::diff_roots(&storage, root_b, root_a).unwrap();
+            assert_eq!(diff, expected);
+        }
+    }
+
+    mod proofs {
+        use crate::storage::Storage;
+        use crate::tree::tests::commit_and_persist;
+        use crate::{verify_proof, Membership, PedersenHash};
+
+        use super::{MerkleTree, TestStorage, TestTree};
+        use bitvec::prelude::Msb0;
+        use bitvec::slice::BitSlice;
+        use pathfinder_common::felt;
+        use rusqlite::Transaction;
+        use stark_hash::Felt;
+
         /// Structure representing a randomly generated tree.
         struct RandomTree<'tx> {
             keys: Vec<Felt>,
@@ -1898,6 +2539,155 @@ mod tests {
             random_tree.verify();
         }
 
+        #[test]
+        fn public_verify_proof() {
+            const LEN: usize = 256;
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let random_tree = RandomTree::new(LEN, &transaction);
+
+            let keys_bits: Vec<&BitSlice<Msb0, u8>> =
+                random_tree.keys.iter().map(|k| k.view_bits()).collect();
+            let proofs = get_proofs(&keys_bits, &random_tree.tree, &random_tree.storage).unwrap();
+
+            for ((key, value), proof) in keys_bits.iter().zip(&random_tree.values).zip(&proofs) {
+                assert_eq!(
+                    verify_proof(random_tree.root, key, *value, proof),
+                    Some(Membership::Member)
+                );
+
+                let wrong_value = *value + felt!("0x1");
+                assert_eq!(
+                    verify_proof(random_tree.root, key, wrong_value, proof),
+                    None
+                );
+            }
+        }
+
+        #[test]
+        fn public_verify_proof_rejects_forged_hashes() {
+            const LEN: usize = 64;
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let random_tree = RandomTree::new(LEN, &transaction);
+
+            let keys_bits: Vec<&BitSlice<Msb0, u8>> =
+                random_tree.keys.iter().map(|k| k.view_bits()).collect();
+            let proofs = get_proofs(&keys_bits, &random_tree.tree, &random_tree.storage).unwrap();
+
+            for ((key, value), proof) in keys_bits.iter().zip(&random_tree.values).zip(&proofs) {
+                // Flip a single hash of each node in turn.
+                for i in 0..proof.len() {
+                    let mut forged = proof.clone();
+                    match &mut forged[i] {
+                        crate::Node::Binary { left, .. } => *left = *left + felt!("0x1"),
+                        crate::Node::Edge { child, .. } => *child = *child + felt!("0x1"),
+                    }
+
+                    assert_eq!(verify_proof(random_tree.root, key, *value, &forged), None);
+                }
+            }
+        }
+
+        #[test]
+        fn public_verify_proof_rejects_malformed_proofs() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let storage = TestStorage::create(&transaction);
+
+            let key = felt!("0x1");
+            let other_key =
+                felt!("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+            let missing_key = felt!("0x2");
+            let value = felt!("0xcc");
+
+            // An empty tree contains no keys.
+            assert_eq!(
+                verify_proof(Felt::ZERO, key.view_bits(), value, &[]),
+                Some(Membership::NonMember)
+            );
+
+            let mut uut = TestTree::empty();
+            uut.set(&storage, key.view_bits(), value).unwrap();
+            uut.set(&storage, other_key.view_bits(), felt!("0xdd"))
+                .unwrap();
+            let root = commit_and_persist(uut, &storage);
+            let uut = TestTree::new(root);
+
+            let proof = uut.get_proof(&storage, key.view_bits()).unwrap();
+            assert_eq!(
+                verify_proof(root, key.view_bits(), value, &proof),
+                Some(Membership::Member)
+            );
+
+            // Proofs which stop short of the leaf.
+            assert_eq!(verify_proof(root, key.view_bits(), value, &[]), None);
+            assert_eq!(
+                verify_proof(root, key.view_bits(), value, &proof[..proof.len() - 1]),
+                None
+            );
+
+            // The proof does not follow the path of another key.
+            assert_eq!(
+                verify_proof(root, other_key.view_bits(), value, &proof),
+                None
+            );
+
+            let non_membership = uut.get_proof(&storage, missing_key.view_bits()).unwrap();
+            assert_eq!(
+                verify_proof(root, missing_key.view_bits(), value, &non_membership),
+                Some(Membership::NonMember)
+            );
+
+            // Proofs which continue after diverging from the key.
+            let mut extended = non_membership;
+            extended.push(proof[proof.len() - 1].clone());
+            assert_eq!(
+                verify_proof(root, missing_key.view_bits(), value, &extended),
+                None
+            );
+        }
+
+        #[test]
+        fn public_verify_proof_rejects_truncated_keys() {
+            let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+            let transaction = conn.transaction().unwrap();
+            let storage = TestStorage::create(&transaction);
+
+            // The keys differ in their first bit, so the root is a binary node.
+            let key = felt!("0x1");
+            let other_key =
+                felt!("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+            let value = felt!("0xcc");
+
+            let mut uut = TestTree::empty();
+            uut.set(&storage, key.view_bits(), value).unwrap();
+            uut.set(&storage, other_key.view_bits(), felt!("0xdd"))
+                .unwrap();
+            let root = commit_and_persist(uut, &storage);
+            let uut = TestTree::new(root);
+
+            let proof = uut.get_proof(&storage, key.view_bits()).unwrap();
+            let left = match &proof[0] {
+                crate::Node::Binary { left, .. } => *left,
+                crate::Node::Edge { .. } => panic!("Root should be a binary node"),
+            };
+
+            // The root's left child is not a leaf, even if a key ends there.
+            assert_eq!(
+                verify_proof(root, &key.view_bits()[..1], left, &proof[..1]),
+                None
+            );
+            assert_eq!(
+                verify_proof(root, &key.view_bits()[..250], value, &proof),
+                None
+            );
+            assert_eq!(
+                verify_proof(Felt::ZERO, &key.view_bits()[..1], value, &[]),
+                None
+            );
+        }
+
         #[test]
         fn non_membership() {
             const LEN: usize = 256;
@@ -1,6 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use pathfinder_common::{ContractRoot, StorageAddress, StorageValue};
-use pathfinder_merkle_tree::ContractsStorageTree;
+use pathfinder_common::{
+    ContractAddress, ContractRoot, ContractStateHash, StorageAddress, StorageCommitment,
+    StorageValue,
+};
+use pathfinder_merkle_tree::{ContractsStorageTree, StorageCommitmentTree};
 use stark_hash::Felt;
 
 fn gen_random_keys(n: usize) -> Vec<Felt> {
@@ -40,6 +43,27 @@ pub fn chunked_inserts(tx: &rusqlite::Transaction<'_>, keys: &[Felt], batch_size
     hash.0
 }
 
+/// Applies the `keys` to an empty [StorageCommitmentTree] one at a time, or as a batch.
+pub fn global_updates(tx: &rusqlite::Transaction<'_>, keys: &[Felt], batch: bool) -> Felt {
+    let mut uut = StorageCommitmentTree::load(tx, StorageCommitment::ZERO);
+
+    let mut updates = keys.iter().enumerate().map(|(value, key)| {
+        (
+            ContractAddress::new_or_panic(*key),
+            ContractStateHash(Felt::from_u64(value as u64 + 1)),
+        )
+    });
+
+    match batch {
+        true => uut.batch_set(updates).unwrap(),
+        false => updates
+            .try_for_each(|(address, value)| uut.set(address, value))
+            .unwrap(),
+    }
+
+    uut.commit_and_persist_changes().unwrap().0
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     let s = pathfinder_storage::Storage::in_memory().unwrap();
     let mut connection = s.connection().unwrap();
@@ -55,6 +79,19 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         )
     });
 
+    // Roughly the number of storage updates of a busy block, which results in tens of
+    // thousands of new nodes being persisted in a single commit.
+    c.bench_function("merkle_tree of 10000", |b| {
+        b.iter_batched_ref(
+            || gen_random_keys(10000),
+            |keys| {
+                let tx = connection.transaction().unwrap();
+                black_box(chunked_inserts(&tx, keys, 10000))
+            },
+            criterion::BatchSize::PerIteration,
+        )
+    });
+
     c.bench_function("merkle_tree of 1000 in 10 batches", |b| {
         b.iter_batched_ref(
             || gen_random_keys(1000),
@@ -78,5 +115,33 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, criterion_benchmark);
+pub fn global_tree_benchmark(c: &mut Criterion) {
+    let s = pathfinder_storage::Storage::in_memory().unwrap();
+    let mut connection = s.connection().unwrap();
+
+    // The keys are random, so the updates are in random order.
+    c.bench_function("global tree 1000 updates one by one", |b| {
+        b.iter_batched_ref(
+            || gen_random_keys(1000),
+            |keys| {
+                let tx = connection.transaction().unwrap();
+                black_box(global_updates(&tx, keys, false))
+            },
+            criterion::BatchSize::PerIteration,
+        )
+    });
+
+    c.bench_function("global tree 1000 updates batched", |b| {
+        b.iter_batched_ref(
+            || gen_random_keys(1000),
+            |keys| {
+                let tx = connection.transaction().unwrap();
+                black_box(global_updates(&tx, keys, true))
+            },
+            criterion::BatchSize::PerIteration,
+        )
+    });
+}
+
+criterion_group!(benches, criterion_benchmark, global_tree_benchmark);
 criterion_main!(benches);
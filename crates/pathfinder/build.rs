@@ -1,7 +1,12 @@
 use std::{path::PathBuf, process::Command};
 
 pub fn main() {
+    // Emitting any `rerun-if` directive disables cargo's default of rerunning the script on
+    // every change to the package, so all of its inputs have to be listed.
+    println!("cargo:rerun-if-changed=build.rs");
+
     set_casm_compiler_version();
+    write_build_info();
 }
 
 #[derive(serde::Deserialize)]
@@ -16,6 +21,10 @@ struct Package {
 }
 
 fn set_casm_compiler_version() {
+    // The compiler version is resolved from the manifest and the workspace's lockfile.
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=../../Cargo.lock");
+
     let manifest_path = PathBuf::from(
         std::env::var_os("CARGO_MANIFEST_DIR")
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "CARGO_MANIFEST_DIR"))
@@ -59,3 +68,55 @@ fn set_casm_compiler_version() {
         sierra_compiler_package.id
     );
 }
+
+/// Writes the build's time to `OUT_DIR/build_info.rs`, see `pathfinder_lib::version_info`.
+///
+/// The git hash is not queried here, it is emitted by `vergen` in the `pathfinder-common` build
+/// script instead.
+fn write_build_info() {
+    rerun_if_git_head_changed();
+
+    let build_timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let build_info = format!(
+        r#"/// Time at which pathfinder was built, in RFC 3339 format.
+pub const BUILD_TIMESTAMP: &str = "{build_timestamp}";
+"#
+    );
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("build_info.rs"), build_info).unwrap();
+}
+
+/// Reruns the build script when a different commit is checked out or the checked out branch
+/// moves.
+fn rerun_if_git_head_changed() {
+    let git_path = |path: &str| {
+        Command::new("git")
+            .args(["rev-parse", "--git-path", path])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|path| PathBuf::from(path.trim()))
+            // Cargo always reruns the script for paths which don't exist.
+            .filter(|path| path.exists())
+    };
+
+    let head = match git_path("HEAD") {
+        Some(head) => head,
+        None => return,
+    };
+    println!("cargo:rerun-if-changed={}", head.display());
+
+    // HEAD only changes when switching branches, new commits update the branch's ref instead.
+    // The ref is either stored in its own file or, after `git gc`, in `packed-refs`.
+    let head = std::fs::read_to_string(head).unwrap_or_default();
+    if let Some(reference) = head.strip_prefix("ref: ") {
+        for path in [reference.trim(), "packed-refs"] {
+            if let Some(path) = git_path(path) {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+    }
+}
@@ -459,6 +459,7 @@ fn resolve_state_update(
 
     let state_update = StateUpdate {
         block_hash,
+        block_number: None,
         new_root,
         old_root,
         state_diff: StateDiff {
@@ -133,6 +133,41 @@ Examples:
         default_value = "1024"
     )]
     max_rpc_connections: std::num::NonZeroU32,
+
+    #[arg(
+        long = "rpc.request-logging",
+        long_help = "Log the method, id, client IP and latency of every RPC call",
+        action = clap::ArgAction::Set,
+        default_value = "false",
+        env = "PATHFINDER_RPC_REQUEST_LOGGING"
+    )]
+    rpc_request_logging: bool,
+
+    #[arg(
+        long = "rpc.max-requests-per-ip",
+        long_help = "Maximum number of RPC requests per second from a single client IP. Clients are identified by the address they connect from, see --rpc.trusted-proxies for clients behind a reverse proxy.",
+        value_name = "REQUESTS",
+        env = "PATHFINDER_RPC_MAX_REQUESTS_PER_IP"
+    )]
+    rpc_max_requests_per_ip: Option<std::num::NonZeroU32>,
+
+    #[arg(
+        long = "rpc.trusted-proxies",
+        long_help = "Comma separated list of reverse proxy IPs whose X-Forwarded-For header is trusted by --rpc.max-requests-per-ip. Requests from these proxies are attributed to the right-most forwarded address which is not a trusted proxy.",
+        value_name = "IP-LIST",
+        value_delimiter = ',',
+        env = "PATHFINDER_RPC_TRUSTED_PROXIES"
+    )]
+    rpc_trusted_proxies: Vec<std::net::IpAddr>,
+
+    #[arg(
+        long = "rpc.health-max-lag",
+        long_help = "Number of blocks pathfinder may lag behind the latest block before the RPC server's `GET /health` endpoint reports it as unhealthy",
+        value_name = "BLOCKS",
+        default_value = "6",
+        env = "PATHFINDER_RPC_HEALTH_MAX_LAG"
+    )]
+    rpc_health_max_lag: u64,
 }
 
 #[derive(clap::Args)]
@@ -279,6 +314,10 @@ pub struct Config {
     pub python_subprocesses: std::num::NonZeroUsize,
     pub sqlite_wal: JournalMode,
     pub max_rpc_connections: std::num::NonZeroU32,
+    pub rpc_request_logging: bool,
+    pub rpc_max_requests_per_ip: Option<std::num::NonZeroU32>,
+    pub rpc_trusted_proxies: Vec<std::net::IpAddr>,
+    pub rpc_health_max_lag: u64,
 }
 
 pub struct WebSocket {
@@ -371,6 +410,10 @@ impl Config {
                 false => JournalMode::Rollback,
             },
             max_rpc_connections: cli.max_rpc_connections,
+            rpc_request_logging: cli.rpc_request_logging,
+            rpc_max_requests_per_ip: cli.rpc_max_requests_per_ip,
+            rpc_trusted_proxies: cli.rpc_trusted_proxies,
+            rpc_health_max_lag: cli.rpc_health_max_lag,
         }
     }
 }
@@ -34,9 +34,12 @@ async fn main() -> anyhow::Result<()> {
 
     let config = config::Config::parse();
 
+    let version_info = pathfinder_lib::version_info();
     info!(
         // this is expected to be $(last_git_tag)-$(commits_since)-$(commit_hash)
         version = VERGEN_GIT_DESCRIBE,
+        git_sha = version_info.git_sha,
+        build_timestamp = version_info.build_timestamp,
         "🏁 Starting node."
     );
 
@@ -121,6 +124,13 @@ async fn main() -> anyhow::Result<()> {
         None => rpc_server,
     };
 
+    let rpc_server = match config.rpc_max_requests_per_ip {
+        Some(max_rps_per_ip) => {
+            rpc_server.with_throttle(max_rps_per_ip, config.rpc_trusted_proxies)
+        }
+        None => rpc_server,
+    };
+
     let rpc_server = match config.ws {
         Some(ws) => rpc_server.with_ws(ws.capacity),
         None => rpc_server,
@@ -145,6 +155,13 @@ async fn main() -> anyhow::Result<()> {
     let (rpc_handle, local_addr) = rpc_server
         .with_logger(RpcMetricsLogger)
         .with_max_connections(config.max_rpc_connections.get())
+        .with_request_logging(config.rpc_request_logging)
+        .with_health_max_lag(config.rpc_health_max_lag)
+        .with_version(pathfinder_rpc::middleware::Version {
+            pathfinder_version: version_info.version,
+            starknet_version: pathfinder_common::consts::STARKNET_VERSION,
+            git_sha: version_info.git_sha,
+        })
         .run()
         .await
         .context("Starting the RPC server")?;
@@ -6,3 +6,26 @@ pub mod state;
 
 #[cfg(feature = "p2p")]
 pub mod p2p_network;
+
+mod build_info {
+    include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
+}
+
+/// Identifies the pathfinder build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// The crate version, e.g. `0.5.5`.
+    pub version: &'static str,
+    /// Short hash of the git commit, or `unknown` if it could not be determined.
+    pub git_sha: &'static str,
+    /// Build time in RFC 3339 format.
+    pub build_timestamp: &'static str,
+}
+
+pub fn version_info() -> VersionInfo {
+    VersionInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: pathfinder_common::consts::VERGEN_GIT_SHA,
+        build_timestamp: build_info::BUILD_TIMESTAMP,
+    }
+}
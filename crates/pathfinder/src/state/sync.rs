@@ -5,9 +5,9 @@ mod pending;
 use anyhow::Context;
 use ethers::types::H160;
 use pathfinder_common::{
-    BlockHash, BlockNumber, Chain, ChainId, ClassCommitment, ClassHash, ContractNonce,
-    ContractRoot, EventCommitment, GasPrice, SequencerAddress, StarknetVersion, StateCommitment,
-    StorageCommitment, TransactionCommitment,
+    BlockHash, BlockNumber, Chain, ChainId, ClassCommitment, ClassHash, ContractAddress,
+    ContractNonce, ContractRoot, EventCommitment, GasPrice, SequencerAddress, StarknetVersion,
+    StateCommitment, StorageCommitment, TransactionCommitment,
 };
 use pathfinder_ethereum::{log::StateUpdateLog, provider::EthereumTransport};
 use pathfinder_merkle_tree::{
@@ -531,14 +531,14 @@ async fn l2_update(
     ev_commitment: EventCommitment,
     state_update: StateUpdate,
 ) -> anyhow::Result<()> {
-    use pathfinder_storage::CanonicalBlocksTable;
+    use pathfinder_storage::{CanonicalBlocksTable, ContractRootHistoryTable};
 
     tokio::task::block_in_place(move || {
         let transaction = connection
             .transaction_with_behavior(TransactionBehavior::Immediate)
             .context("Create database transaction")?;
 
-        let (new_storage_commitment, new_class_commitment) =
+        let (new_storage_commitment, new_class_commitment, new_contract_roots) =
             update_starknet_state(&transaction, &state_update)
                 .context("Updating Starknet state")?;
         let new_root = StateCommitment::calculate(new_storage_commitment, new_class_commitment);
@@ -574,6 +574,16 @@ async fn l2_update(
         CanonicalBlocksTable::insert(&transaction, block.block_number, block.block_hash)
             .context("Inserting canonical block into database")?;
 
+        for (contract_address, root) in new_contract_roots {
+            ContractRootHistoryTable::insert(
+                &transaction,
+                block.block_number,
+                contract_address,
+                root,
+            )
+            .context("Inserting contract root history")?;
+        }
+
         let rpc_state_update: pathfinder_storage::types::StateUpdate = state_update.into();
 
         let declared_sierra_class_hashes = rpc_state_update
@@ -682,10 +692,16 @@ async fn l2_reorg(connection: &mut Connection, reorg_tail: BlockNumber) -> anyho
     })
 }
 
+/// Returns the new storage and class commitments, as well as the new storage roots of all
+/// contracts with storage updates.
 fn update_starknet_state(
     transaction: &Transaction<'_>,
     state_update: &StateUpdate,
-) -> anyhow::Result<(StorageCommitment, ClassCommitment)> {
+) -> anyhow::Result<(
+    StorageCommitment,
+    ClassCommitment,
+    Vec<(ContractAddress, ContractRoot)>,
+)> {
     let (storage_commitment, class_commitment) =
         StarknetBlocksTable::get_state_commitment(transaction, StarknetBlocksBlockId::Latest)
             .context("Query latest state commitment")?
@@ -708,6 +724,8 @@ fn update_starknet_state(
         .map(|r| (r.address, r.class_hash))
         .collect::<HashMap<_, _>>();
 
+    let mut new_contract_roots = Vec::new();
+
     // Apply contract storage updates.
     for (contract_address, updates) in &state_update.state_diff.storage_diffs {
         // Remove the nonce so we don't update it again in the next stage.
@@ -725,6 +743,11 @@ fn update_starknet_state(
         )
         .context("Update contract state")?;
 
+        let root = ContractsStateTable::get_root(transaction, contract_state_hash)
+            .context("Read contract root from contracts state table")?
+            .context("Contract state is missing")?;
+        new_contract_roots.push((*contract_address, root));
+
         // Update the global state tree.
         storage_commitment_tree
             .set(*contract_address, contract_state_hash)
@@ -800,7 +823,11 @@ fn update_starknet_state(
         .commit_and_persist_changes()
         .context("Apply class commitment tree updates")?;
 
-    Ok((new_storage_commitment, new_class_commitment))
+    Ok((
+        new_storage_commitment,
+        new_class_commitment,
+        new_contract_roots,
+    ))
 }
 
 fn deploy_contract(
@@ -1249,6 +1276,7 @@ mod tests {
         // Causes root to remain unchanged
         pub static ref STATE_UPDATE0: reply::StateUpdate = reply::StateUpdate {
             block_hash: BlockHash(*A),
+            block_number: None,
             new_root: *STATE_COMMITMENT0,
             old_root: *STATE_COMMITMENT0,
             state_diff: reply::state_update::StateDiff{
@@ -790,6 +790,7 @@ mod tests {
 
             static ref STATE_UPDATE0: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK0_HASH,
+                block_number: None,
                 new_root: *GLOBAL_ROOT0,
                 old_root: StateCommitment(Felt::ZERO),
                 state_diff: reply::state_update::StateDiff {
@@ -812,6 +813,7 @@ mod tests {
             };
             static ref STATE_UPDATE0_V2: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK0_HASH_V2,
+                block_number: None,
                 new_root: *GLOBAL_ROOT0_V2,
                 old_root: StateCommitment(Felt::ZERO),
                 state_diff: reply::state_update::StateDiff {
@@ -828,6 +830,7 @@ mod tests {
             };
             static ref STATE_UPDATE1: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK1_HASH,
+                block_number: None,
                 new_root: *GLOBAL_ROOT1,
                 old_root: *GLOBAL_ROOT0,
                 state_diff: reply::state_update::StateDiff {
@@ -859,6 +862,7 @@ mod tests {
             };
             static ref STATE_UPDATE1_V2: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK1_HASH_V2,
+                block_number: None,
                 new_root: *GLOBAL_ROOT1_V2,
                 old_root: *GLOBAL_ROOT0_V2,
                 state_diff: reply::state_update::StateDiff {
@@ -872,6 +876,7 @@ mod tests {
             };
             static ref STATE_UPDATE2: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK2_HASH,
+                block_number: None,
                 new_root: *GLOBAL_ROOT2,
                 old_root: *GLOBAL_ROOT1,
                 state_diff: reply::state_update::StateDiff {
@@ -885,6 +890,7 @@ mod tests {
             };
             static ref STATE_UPDATE2_V2: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK2_HASH_V2,
+                block_number: None,
                 new_root: *GLOBAL_ROOT2_V2,
                 old_root: *GLOBAL_ROOT1_V2,
                 state_diff: reply::state_update::StateDiff {
@@ -898,6 +904,7 @@ mod tests {
             };
             static ref STATE_UPDATE3: reply::StateUpdate = reply::StateUpdate {
                 block_hash: *BLOCK3_HASH,
+                block_number: None,
                 new_root: *GLOBAL_ROOT3,
                 old_root: *GLOBAL_ROOT2,
                 state_diff: reply::state_update::StateDiff {
@@ -8,6 +8,8 @@ use starknet_gateway_types::reply::{Block, StateUpdate};
 /// - `pending` is a fully formed block and not [PendingBlock](starknet_gateway_types::reply::MaybePendingBlock::Pending), or
 /// - the state update parent root does not match head.
 ///
+/// Pending data is only emitted if it differs from the previously emitted pending data.
+///
 /// A full block or full state update can be returned from this function if it is encountered during polling.
 pub async fn poll_pending(
     tx_event: tokio::sync::mpsc::Sender<super::l2::Event>,
@@ -22,6 +24,8 @@ pub async fn poll_pending(
     use pathfinder_common::BlockId;
     use std::sync::Arc;
 
+    let mut previous = None;
+
     loop {
         use starknet_gateway_types::reply::{MaybePendingBlock, MaybePendingStateUpdate};
 
@@ -81,15 +85,23 @@ pub async fn poll_pending(
                     return Ok((None, None));
                 }
 
-                // Emit new pending data.
-                use crate::state::l2::Event::Pending;
-                tx_event
-                    .send(Pending(
-                        Arc::new(pending_block),
-                        Arc::new(pending_state_update),
-                    ))
-                    .await
-                    .context("Event channel closed")?;
+                let unchanged = matches!(
+                    &previous,
+                    Some((block, state_update)) if **block == pending_block && **state_update == pending_state_update
+                );
+                if unchanged {
+                    tracing::trace!("Pending data unchanged");
+                } else {
+                    // Emit new pending data.
+                    use crate::state::l2::Event::Pending;
+                    let pending_block = Arc::new(pending_block);
+                    let pending_state_update = Arc::new(pending_state_update);
+                    tx_event
+                        .send(Pending(pending_block.clone(), pending_state_update.clone()))
+                        .await
+                        .context("Event channel closed")?;
+                    previous = Some((pending_block, pending_state_update));
+                }
 
                 tokio::time::sleep(poll_interval).await;
             }
@@ -198,6 +210,7 @@ mod tests {
         let pending_diff = PENDING_DIFF.clone();
         let full_diff = StateUpdate {
             block_hash: NEXT_BLOCK.block_hash,
+            block_number: None,
             new_root: StateCommitment(felt!("0x12")),
             old_root: pending_diff.old_root,
             state_diff: pending_diff.state_diff,
@@ -323,4 +336,42 @@ mod tests {
         use crate::state::l2::Event::Pending;
         assert_matches!(result, Pending(block, diff) if *block == *PENDING_BLOCK && *diff == *PENDING_DIFF);
     }
+
+    #[tokio::test]
+    async fn unchanged_data_is_emitted_once() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let mut sequencer = MockGatewayApi::new();
+
+        sequencer
+            .expect_block()
+            .returning(move |_| Ok(MaybePendingBlock::Pending(PENDING_BLOCK.clone())));
+        sequencer
+            .expect_state_update()
+            .returning(move |_| Ok(MaybePendingStateUpdate::Pending(PENDING_DIFF.clone())));
+
+        let _jh = tokio::spawn(async move {
+            poll_pending(
+                tx,
+                &sequencer,
+                (*PARENT_HASH, *PARENT_ROOT),
+                std::time::Duration::from_millis(1),
+            )
+            .await
+        });
+
+        let result = tokio::time::timeout(TEST_TIMEOUT, rx.recv())
+            .await
+            .expect("Event should be emitted")
+            .unwrap();
+
+        use crate::state::l2::Event::Pending;
+        assert_matches!(result, Pending(block, diff) if *block == *PENDING_BLOCK && *diff == *PENDING_DIFF);
+
+        // The sequencer keeps returning the same pending data, which should not be re-emitted.
+        let result = tokio::time::timeout(std::time::Duration::from_millis(100), rx.recv()).await;
+        assert!(
+            result.is_err(),
+            "Unchanged pending data should not be emitted"
+        );
+    }
 }
@@ -29,6 +29,7 @@ use tokio::sync::RwLock;
 use tower_http::cors::CorsLayer;
 
 const DEFAULT_MAX_CONNECTIONS: u32 = 1024;
+const DEFAULT_HEALTH_MAX_LAG: u64 = 6;
 
 pub struct RpcServer {
     addr: SocketAddr,
@@ -36,6 +37,10 @@ pub struct RpcServer {
     logger: MaybeRpcMetricsLogger,
     max_connections: u32,
     cors: Option<CorsLayer>,
+    health_max_lag: u64,
+    request_logging: bool,
+    throttle: Option<middleware::Throttle>,
+    version: Option<middleware::Version>,
     ws_senders: Option<WebsocketSenders>,
 }
 
@@ -47,6 +52,10 @@ impl RpcServer {
             logger: MaybeRpcMetricsLogger::NoOp,
             max_connections: DEFAULT_MAX_CONNECTIONS,
             cors: None,
+            health_max_lag: DEFAULT_HEALTH_MAX_LAG,
+            request_logging: false,
+            throttle: None,
+            version: None,
             ws_senders: None,
         }
     }
@@ -77,6 +86,48 @@ impl RpcServer {
         }
     }
 
+    /// Sets the number of blocks the node may lag behind before `GET /health` reports it
+    /// as unhealthy, see [middleware::HealthCheck].
+    pub fn with_health_max_lag(self, max_lag: u64) -> Self {
+        Self {
+            health_max_lag: max_lag,
+            ..self
+        }
+    }
+
+    /// Logs every RPC call and its latency, see [middleware::RequestLogger].
+    pub fn with_request_logging(self, enabled: bool) -> Self {
+        Self {
+            request_logging: enabled,
+            ..self
+        }
+    }
+
+    /// Limits the number of requests per second of each client, see [middleware::Throttle].
+    ///
+    /// Requests from `trusted_proxies` are attributed to the client in their `X-Forwarded-For`
+    /// header.
+    pub fn with_throttle(
+        self,
+        max_rps_per_ip: std::num::NonZeroU32,
+        trusted_proxies: Vec<std::net::IpAddr>,
+    ) -> Self {
+        Self {
+            throttle: Some(
+                middleware::Throttle::new(max_rps_per_ip).with_trusted_proxies(trusted_proxies),
+            ),
+            ..self
+        }
+    }
+
+    /// Serves `GET /version` with the given version information, see [middleware::Version].
+    pub fn with_version(self, version: middleware::Version) -> Self {
+        Self {
+            version: Some(version),
+            ..self
+        }
+    }
+
     /// Starts the HTTP-RPC server.
     pub async fn run(self) -> Result<(ServerHandle, SocketAddr), anyhow::Error> {
         const TEN_MB: u32 = 10 * 1024 * 1024;
@@ -90,6 +141,11 @@ impl RpcServer {
             .set_logger(self.logger)
             .set_middleware(tower::ServiceBuilder::new()
                 .option_layer(self.cors)
+                .layer(middleware::HealthCheck::new(
+                    self.context.sync_status.clone(),
+                    self.health_max_lag,
+                ))
+                .option_layer(self.version)
                 .map_result(middleware::versioning::try_map_errors_to_responses)
                 .filter_async(
 					|result: Request<Body>| async move {
@@ -99,6 +155,9 @@ impl RpcServer {
 					}
                     middleware::versioning::prefix_rpc_method_names_with_version(result, TEN_MB).await
                 })
+                .option_layer(self.request_logging.then(|| middleware::RequestLogger::new(TEN_MB)))
+                // Must be the innermost layer to see the peer address.
+                .option_layer(self.throttle)
             )
             .build(self.addr)
             .await
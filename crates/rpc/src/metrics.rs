@@ -85,10 +85,13 @@ pub mod logger {
 
         fn on_connect(
             &self,
-            _remote_addr: std::net::SocketAddr,
-            _request: &jsonrpsee::server::logger::HttpRequest,
+            remote_addr: std::net::SocketAddr,
+            request: &jsonrpsee::server::logger::HttpRequest,
             _transport: jsonrpsee::server::logger::TransportProtocol,
         ) {
+            // This is the server's logger whether metrics are enabled or not, and the only
+            // place where the peer address is available.
+            crate::middleware::peer_addr::PeerAddr::record(request, remote_addr);
         }
 
         fn on_request(
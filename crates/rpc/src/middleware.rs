@@ -1,2 +1,12 @@
 pub mod cors;
+pub mod health;
+pub(crate) mod peer_addr;
+pub mod request_logger;
+pub mod throttle;
+pub mod version;
 pub mod versioning;
+
+pub use health::HealthCheck;
+pub use request_logger::RequestLogger;
+pub use throttle::Throttle;
+pub use version::Version;
@@ -0,0 +1,159 @@
+//! Middleware which serves the `GET /health` endpoint next to the JSON-RPC API.
+use crate::v02::types::syncing::Syncing;
+use crate::SyncState;
+use futures::future::BoxFuture;
+use http::{response::Builder, status::StatusCode, Method};
+use hyper::{Body, Request, Response};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{BoxError, Layer, Service};
+
+/// [Layer] which answers `GET /health` based on the sync status.
+///
+/// Responds with `200 OK` and `{"status":"ok","sync_height":N}` if the node is at most
+/// `max_lag` blocks behind the latest block, and with `503 Service Unavailable` and
+/// `{"status":"syncing","lag":N}` otherwise. The lag is omitted if it is not known yet.
+#[derive(Clone)]
+pub struct HealthCheck {
+    sync_state: Arc<SyncState>,
+    max_lag: u64,
+}
+
+impl HealthCheck {
+    pub fn new(sync_state: Arc<SyncState>, max_lag: u64) -> Self {
+        Self {
+            sync_state,
+            max_lag,
+        }
+    }
+
+    async fn response(&self) -> Response<Body> {
+        let (status, body) = match &*self.sync_state.status.read().await {
+            Syncing::Status(status) => {
+                let height = status.current.number.get();
+                let lag = status.highest.number.get().saturating_sub(height);
+
+                if lag > self.max_lag {
+                    (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        serde_json::json!({"status": "syncing", "lag": lag}),
+                    )
+                } else {
+                    (
+                        StatusCode::OK,
+                        serde_json::json!({"status": "ok", "sync_height": height}),
+                    )
+                }
+            }
+            // Sync has not determined the latest block yet.
+            Syncing::False(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                serde_json::json!({"status": "syncing"}),
+            ),
+        };
+
+        Builder::new()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string().into())
+            .expect("response is properly formed")
+    }
+}
+
+impl<S> Layer<S> for HealthCheck {
+    type Service = HealthCheckService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HealthCheckService {
+            inner,
+            health: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HealthCheckService<S> {
+    inner: S,
+    health: HealthCheck,
+}
+
+impl<S> Service<Request<Body>> for HealthCheckService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() == Method::GET && request.uri().path() == "/health" {
+            let health = self.health.clone();
+            return Box::pin(async move { Ok(health.response().await) });
+        }
+
+        // The service which was driven to readiness is the one which must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move { inner.call(request).await.map_err(Into::into) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v02::types::syncing::{NumberedBlock, Status};
+    use pathfinder_common::{BlockHash, BlockNumber};
+    use stark_hash::Felt;
+
+    fn sync_state(current: u64, highest: u64) -> Arc<SyncState> {
+        let block = |number| {
+            NumberedBlock::from((BlockHash(Felt::ZERO), BlockNumber::new_or_panic(number)))
+        };
+
+        let state = SyncState::default();
+        *state.status.try_write().unwrap() = Syncing::Status(Status {
+            starting: block(0),
+            current: block(current),
+            highest: block(highest),
+        });
+        Arc::new(state)
+    }
+
+    async fn check(health: HealthCheck) -> (StatusCode, serde_json::Value) {
+        let response = health.response().await;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn healthy() {
+        let (status, body) = check(HealthCheck::new(sync_state(100, 105), 5)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            serde_json::json!({"status": "ok", "sync_height": 100})
+        );
+    }
+
+    #[tokio::test]
+    async fn lagging() {
+        let (status, body) = check(HealthCheck::new(sync_state(100, 106), 5)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, serde_json::json!({"status": "syncing", "lag": 6}));
+    }
+
+    #[tokio::test]
+    async fn sync_not_started() {
+        let (status, body) = check(HealthCheck::new(Arc::new(SyncState::default()), 5)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, serde_json::json!({"status": "syncing"}));
+    }
+}
@@ -0,0 +1,40 @@
+//! Makes the socket peer address of a request available to the middleware.
+//!
+//! jsonrpsee accepts the connections itself and only passes the peer address on to its
+//! [Logger](jsonrpsee::server::logger::Logger). Its `on_connect` is called synchronously by
+//! the innermost service when it is called with a request, before anything is processed.
+//! The server's logger [records](PeerAddr::record) the address in the request's [PeerAddr],
+//! which a middleware [inserts](PeerAddr::of) before calling its inner service.
+use hyper::Request;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Slot for the peer address of a request, shared between the request's extensions and
+/// the middleware which inserted it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PeerAddr(Arc<Mutex<Option<SocketAddr>>>);
+
+impl PeerAddr {
+    /// Returns the request's slot, inserting an empty one if it has none yet.
+    pub(crate) fn of<B>(request: &mut Request<B>) -> Self {
+        if let Some(slot) = request.extensions().get::<PeerAddr>() {
+            return slot.clone();
+        }
+
+        let slot = PeerAddr::default();
+        request.extensions_mut().insert(slot.clone());
+        slot
+    }
+
+    /// Records `addr` in the request's slot, if a middleware inserted one.
+    pub(crate) fn record<B>(request: &Request<B>, addr: SocketAddr) {
+        if let Some(slot) = request.extensions().get::<PeerAddr>() {
+            *slot.0.lock().unwrap() = Some(addr);
+        }
+    }
+
+    /// The peer address, known once the innermost service has been called.
+    pub(crate) fn get(&self) -> Option<SocketAddr> {
+        *self.0.lock().unwrap()
+    }
+}
@@ -0,0 +1,181 @@
+//! Middleware that logs every JSON-RPC call together with its latency.
+use super::peer_addr::PeerAddr;
+use futures::future::BoxFuture;
+use hyper::{Body, Request, Response};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::{BoxError, Layer, Service};
+
+/// [Layer] which logs the method name, request id, peer address and latency of each
+/// JSON-RPC call, along with the HTTP status or error of the response.
+///
+/// The `X-Forwarded-For` header is logged as is. It is set by the client unless the
+/// server is behind a reverse proxy which overwrites it.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestLogger {
+    max_request_body_size: u32,
+}
+
+impl RequestLogger {
+    /// Requests with a body larger than `max_request_body_size` are rejected, like the
+    /// server does.
+    pub fn new(max_request_body_size: u32) -> Self {
+        Self {
+            max_request_body_size,
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestLogger {
+    type Service = RequestLoggerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestLoggerService {
+            inner,
+            max_request_body_size: self.max_request_body_size,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestLoggerService<S> {
+    inner: S,
+    max_request_body_size: u32,
+}
+
+impl<S> Service<Request<Body>> for RequestLoggerService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        // The service which was driven to readiness is the one which must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let max_request_body_size = self.max_request_body_size;
+
+        Box::pin(async move {
+            // Websocket handshakes carry no calls.
+            if request.headers().get("sec-websocket-key").is_some() {
+                return inner.call(request).await.map_err(Into::into);
+            }
+
+            let started_at = Instant::now();
+            let peer_addr = PeerAddr::of(&mut request);
+            let forwarded_for = request
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned);
+
+            let (parts, body) = request.into_parts();
+            let (body, _) =
+                super::versioning::read_limited_body(&parts.headers, body, max_request_body_size)
+                    .await?;
+            let calls = calls(&body);
+            let request = Request::from_parts(parts, body.into());
+
+            let result = inner.call(request).await.map_err(Into::into);
+            let latency_us = started_at.elapsed().as_micros() as u64;
+            let peer_addr = peer_addr
+                .get()
+                .map(|addr| addr.to_string())
+                .unwrap_or_else(|| "unknown".to_owned());
+            let forwarded_for = forwarded_for.as_deref();
+
+            for (method, id) in &calls {
+                match &result {
+                    Ok(response) => tracing::info!(
+                        %method, %id, %peer_addr, forwarded_for, latency_us,
+                        status=%response.status(), "RPC call"
+                    ),
+                    Err(error) => tracing::info!(
+                        %method, %id, %peer_addr, forwarded_for, latency_us, %error,
+                        "RPC call failed"
+                    ),
+                }
+            }
+
+            result
+        })
+    }
+}
+
+/// Extracts the method name and id of each call in a single or batch request.
+///
+/// Malformed requests contain no calls, they are rejected by the server anyway.
+fn calls(body: &[u8]) -> Vec<(String, serde_json::Value)> {
+    #[derive(serde::Deserialize)]
+    struct Call {
+        method: String,
+        // Absent for notifications.
+        #[serde(default)]
+        id: serde_json::Value,
+    }
+
+    let calls = match serde_json::from_slice::<Call>(body) {
+        Ok(call) => vec![call],
+        Err(_) => serde_json::from_slice::<Vec<Call>>(body).unwrap_or_default(),
+    };
+
+    calls
+        .into_iter()
+        .map(|call| (call.method, call.id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn single_and_batch_calls() {
+        let single = br#"{"jsonrpc":"2.0","method":"starknet_chainId","id":1}"#;
+        assert_eq!(
+            calls(single),
+            vec![("starknet_chainId".to_owned(), json!(1))]
+        );
+
+        let batch = br#"[
+            {"jsonrpc":"2.0","method":"starknet_chainId","id":"a"},
+            {"jsonrpc":"2.0","method":"starknet_syncing"}
+        ]"#;
+        assert_eq!(
+            calls(batch),
+            vec![
+                ("starknet_chainId".to_owned(), json!("a")),
+                ("starknet_syncing".to_owned(), serde_json::Value::Null),
+            ]
+        );
+
+        assert!(calls(b"not json").is_empty());
+    }
+
+    #[tokio::test]
+    async fn body_size_is_limited() {
+        let server = tower::service_fn(|_: Request<Body>| {
+            futures::future::ready(Ok::<_, BoxError>(Response::new(Body::empty())))
+        });
+        let mut logger = RequestLogger::new(16).layer(server);
+
+        let request = Request::new(Body::from(r#"{"method":"a"}"#));
+        let response = logger.call(request).await.unwrap();
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        let request = Request::new(Body::from(r#"{"method":"starknet_chainId"}"#));
+        let error = logger.call(request).await.unwrap_err();
+        let response =
+            crate::middleware::versioning::try_map_errors_to_responses(Err(error)).unwrap();
+        assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
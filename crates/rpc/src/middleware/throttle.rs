@@ -0,0 +1,367 @@
+//! Middleware which rate-limits RPC requests per client IP address.
+use super::peer_addr::PeerAddr;
+use futures::future::BoxFuture;
+use http::{response::Builder, status::StatusCode, HeaderMap};
+use hyper::{Body, Request, Response};
+use jsonrpsee::types::{ErrorObject, ErrorResponse, Id};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{BoxError, Layer, Service};
+
+/// JSON-RPC error code returned for throttled requests.
+const RATE_LIMITED: i32 = -32005;
+
+/// Number of tracked clients above which idle entries are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Minimum time between two prunes, so that a large number of active clients doesn't cause
+/// a full scan on every request.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// [Layer] which limits each client IP to `max_rps_per_ip` requests per second.
+///
+/// Requests over budget are rejected with HTTP 429, a `Retry-After` header and the
+/// JSON-RPC error `-32005`.
+///
+/// Clients are identified by their socket peer address, which is only known once the
+/// server has been called with the request, see [peer_addr](super::peer_addr). This layer
+/// must therefore be the innermost one. Requests from [trusted proxies](Self::with_trusted_proxies)
+/// are attributed to the client the proxy forwarded them for.
+#[derive(Clone, Debug)]
+pub struct Throttle {
+    max_rps_per_ip: u32,
+    trusted_proxies: HashSet<IpAddr>,
+    store: Arc<Mutex<Store>>,
+}
+
+/// The token buckets of the clients seen recently.
+#[derive(Debug, Default)]
+struct Store {
+    buckets: HashMap<IpAddr, TokenBucket>,
+    last_prune: Option<Instant>,
+}
+
+impl Throttle {
+    pub fn new(max_rps_per_ip: NonZeroU32) -> Self {
+        Self {
+            max_rps_per_ip: max_rps_per_ip.get(),
+            trusted_proxies: Default::default(),
+            store: Default::default(),
+        }
+    }
+
+    /// Trusts the `X-Forwarded-For` header of requests from these reverse proxies.
+    ///
+    /// Each proxy appends the address it received the request from, so the right-most
+    /// entry which isn't a trusted proxy is the client. Entries to the left of it are
+    /// set by the client itself and are ignored.
+    pub fn with_trusted_proxies(self, proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self {
+            trusted_proxies: proxies.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Identifies the client of a request received from `peer`.
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.trusted_proxies.contains(&peer) {
+            return peer;
+        }
+
+        let forwarded_for = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+
+        let mut client = peer;
+        for entry in forwarded_for.into_iter().rev() {
+            match entry.trim().parse::<IpAddr>() {
+                Ok(ip) => client = ip,
+                // Not appended by a trusted proxy, attribute the request to the proxy.
+                Err(_) => break,
+            }
+            if !self.trusted_proxies.contains(&client) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Takes a token from the client's bucket, or returns how long to wait for one.
+    fn acquire(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut store = self.store.lock().unwrap();
+
+        let prune_due = store
+            .last_prune
+            .map_or(true, |last| now.duration_since(last) >= PRUNE_INTERVAL);
+        if store.buckets.len() > PRUNE_THRESHOLD && prune_due {
+            // Buckets which have been idle for a second are full again, and are
+            // equivalent to a fresh one.
+            store.buckets.retain(|_, bucket| {
+                now.duration_since(bucket.last_refill) < Duration::from_secs(1)
+            });
+            store.last_prune = Some(now);
+        }
+
+        store
+            .buckets
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::full(self.max_rps_per_ip, now))
+            .try_take(self.max_rps_per_ip, now)
+    }
+}
+
+impl<S> Layer<S> for Throttle {
+    type Service = ThrottleService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ThrottleService {
+            inner,
+            throttle: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ThrottleService<S> {
+    inner: S,
+    throttle: Throttle,
+}
+
+impl<S> Service<Request<Body>> for ThrottleService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let peer_addr = PeerAddr::of(&mut request);
+        let headers = request.headers().clone();
+
+        // Calling the server records the peer address. The request is only processed
+        // once the returned future is polled, and a dropped websocket handshake never
+        // upgrades the connection.
+        let response = self.inner.call(request);
+
+        // Only absent if the server rejected the request outright.
+        if let Some(peer_addr) = peer_addr.get() {
+            let ip = self.throttle.client_ip(peer_addr.ip(), &headers);
+            if let Err(retry_after) = self.throttle.acquire(ip, Instant::now()) {
+                return Box::pin(futures::future::ready(Ok(rate_limited(retry_after))));
+            }
+        }
+
+        Box::pin(async move { response.await.map_err(Into::into) })
+    }
+}
+
+/// Holds up to one second's worth of requests, refilled continuously.
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(rate: u32, now: Instant) -> Self {
+        Self {
+            tokens: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn try_take(&mut self, rate: u32, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate as f64))
+        }
+    }
+}
+
+fn rate_limited(retry_after: Duration) -> Response<Body> {
+    // Retry-After only supports whole seconds.
+    let retry_after = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    let error = ErrorObject::owned(RATE_LIMITED, "Rate limit exceeded", None::<()>);
+    let body = serde_json::to_string(&ErrorResponse::borrowed(error, Id::Null))
+        .expect("error response is serializable");
+
+    Builder::new()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(
+            http::header::CONTENT_TYPE,
+            "application/json; charset=utf-8",
+        )
+        .header(http::header::RETRY_AFTER, retry_after)
+        .body(body.into())
+        .expect("response is properly formed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_is_per_ip_and_refills() {
+        let throttle = Throttle::new(NonZeroU32::new(2).unwrap());
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+        let now = Instant::now();
+
+        throttle.acquire(a, now).unwrap();
+        throttle.acquire(a, now).unwrap();
+        let retry_after = throttle.acquire(a, now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        // Other clients have their own budget.
+        throttle.acquire(b, now).unwrap();
+
+        throttle
+            .acquire(a, now + Duration::from_millis(500))
+            .unwrap();
+        throttle
+            .acquire(a, now + Duration::from_millis(500))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn idle_buckets_are_pruned_at_most_once_per_interval() {
+        let throttle = Throttle::new(NonZeroU32::new(1).unwrap());
+        let ip = |i: usize| IpAddr::from((i as u128).to_be_bytes());
+        let tracked = || throttle.store.lock().unwrap().buckets.len();
+        let now = Instant::now();
+
+        for i in 0..=PRUNE_THRESHOLD {
+            throttle.acquire(ip(i), now).unwrap();
+        }
+        assert_eq!(tracked(), PRUNE_THRESHOLD + 1);
+
+        // All of them are idle by now, only the new client remains.
+        let now = now + PRUNE_INTERVAL;
+        throttle.acquire(ip(0), now).unwrap();
+        assert_eq!(tracked(), 1);
+
+        // The next prune is only due after another interval.
+        for i in 1..=PRUNE_THRESHOLD {
+            throttle.acquire(ip(i), now).unwrap();
+        }
+        let later = now + PRUNE_INTERVAL / 2;
+        throttle.acquire(ip(PRUNE_THRESHOLD + 1), later).unwrap();
+        assert_eq!(tracked(), PRUNE_THRESHOLD + 2);
+
+        throttle.acquire(ip(0), now + PRUNE_INTERVAL).unwrap();
+        assert_eq!(tracked(), 2);
+    }
+
+    #[test]
+    fn client_is_the_peer_unless_forwarded_by_a_trusted_proxy() {
+        let ip = |ip: &str| ip.parse::<IpAddr>().unwrap();
+        let throttle = Throttle::new(NonZeroU32::new(1).unwrap())
+            .with_trusted_proxies([ip("10.0.0.1"), ip("10.0.0.2")]);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.1.1.1".parse().unwrap());
+        assert_eq!(throttle.client_ip(ip("2.2.2.2"), &headers), ip("2.2.2.2"));
+        assert_eq!(throttle.client_ip(ip("10.0.0.1"), &headers), ip("1.1.1.1"));
+
+        // Entries left of the one appended by the proxies are set by the client.
+        headers.insert(
+            "x-forwarded-for",
+            "3.3.3.3, 1.1.1.1, 10.0.0.2".parse().unwrap(),
+        );
+        assert_eq!(throttle.client_ip(ip("10.0.0.1"), &headers), ip("1.1.1.1"));
+
+        headers.insert("x-forwarded-for", "garbage".parse().unwrap());
+        assert_eq!(throttle.client_ip(ip("10.0.0.1"), &headers), ip("10.0.0.1"));
+
+        headers.remove("x-forwarded-for");
+        assert_eq!(throttle.client_ip(ip("10.0.0.1"), &headers), ip("10.0.0.1"));
+    }
+
+    #[tokio::test]
+    async fn throttles_by_peer_address() {
+        // Records the peer address like the server's logger does.
+        let server = |peer: &'static str| {
+            tower::service_fn(move |request: Request<Body>| {
+                PeerAddr::record(&request, peer.parse().unwrap());
+                futures::future::ready(Ok::<_, BoxError>(Response::new(Body::empty())))
+            })
+        };
+        let request = || {
+            Request::builder()
+                .header("x-forwarded-for", "1.1.1.1")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let throttle = Throttle::new(NonZeroU32::new(1).unwrap());
+
+        let mut a = throttle.layer(server("10.0.0.1:1234"));
+        let response = a.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        // The forwarded address is spoofable and ignored.
+        let response = a.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let mut b = throttle.layer(server("10.0.0.2:1234"));
+        let response = b.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn server_throttles_by_peer_address() {
+        let context = crate::RpcContext::for_tests();
+        let (_server_handle, address) =
+            crate::RpcServer::new("127.0.0.1:0".parse().unwrap(), context)
+                .with_throttle(NonZeroU32::new(1).unwrap(), vec![])
+                .run()
+                .await
+                .unwrap();
+
+        let url = format!("http://{address}/rpc/v0.3");
+        let request = |forwarded_for: &str| {
+            reqwest::Client::new()
+                .post(&url)
+                .header("content-type", "application/json")
+                .header("x-forwarded-for", forwarded_for)
+                .body(r#"{"jsonrpc":"2.0","method":"starknet_chainId","id":1}"#)
+                .send()
+        };
+
+        let response = request("1.1.1.1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = request("2.2.2.2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response() {
+        let response = rate_limited(Duration::from_millis(1500));
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[http::header::RETRY_AFTER], "2");
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(body["error"]["code"], RATE_LIMITED);
+    }
+}
@@ -0,0 +1,98 @@
+//! Middleware which serves the `GET /version` endpoint next to the JSON-RPC API.
+use futures::future::BoxFuture;
+use http::{response::Builder, status::StatusCode, Method};
+use hyper::{Body, Request, Response};
+use std::task::{Context, Poll};
+use tower::{BoxError, Layer, Service};
+
+/// [Layer] which answers `GET /version` with this build's version information as JSON.
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+pub struct Version {
+    pub pathfinder_version: &'static str,
+    pub starknet_version: &'static str,
+    pub git_sha: &'static str,
+}
+
+impl Version {
+    fn response(&self) -> Response<Body> {
+        let body = serde_json::to_string(self).expect("version is serializable");
+
+        Builder::new()
+            .status(StatusCode::OK)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body.into())
+            .expect("response is properly formed")
+    }
+}
+
+impl<S> Layer<S> for Version {
+    type Service = VersionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VersionService {
+            inner,
+            version: *self,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VersionService<S> {
+    inner: S,
+    version: Version,
+}
+
+impl<S> Service<Request<Body>> for VersionService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.method() == Method::GET && request.uri().path() == "/version" {
+            return Box::pin(futures::future::ready(Ok(self.version.response())));
+        }
+
+        // The service which was driven to readiness is the one which must be called.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move { inner.call(request).await.map_err(Into::into) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn response() {
+        let version = Version {
+            pathfinder_version: "0.5.5",
+            starknet_version: "0.11.1",
+            git_sha: "abc123",
+        };
+
+        let response = version.response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "pathfinder_version": "0.5.5",
+                "starknet_version": "0.11.1",
+                "git_sha": "abc123",
+            })
+        );
+    }
+}
@@ -51,20 +51,7 @@ pub(crate) async fn prefix_rpc_method_names_with_version(
     // Retain the parts to then later recreate the request
     let (parts, body) = request.into_parts();
 
-    let (body, is_single) = match read_body(&parts.headers, body, max_request_body_size).await {
-        Ok(x) => x,
-        Err(GenericTransportError::TooLarge) => {
-            return Err(BoxError::from(VersioningError::TooLarge(
-                max_request_body_size,
-            )))
-        }
-        Err(GenericTransportError::Malformed) => {
-            return Err(BoxError::from(VersioningError::Malformed))
-        }
-        Err(GenericTransportError::Inner(_)) => {
-            return Err(BoxError::from(VersioningError::Internal))
-        }
-    };
+    let (body, is_single) = read_limited_body(&parts.headers, body, max_request_body_size).await?;
 
     let new_body = if is_single {
         match serde_json::from_slice::<jsonrpsee::types::Request<'_>>(&body) {
@@ -101,6 +88,25 @@ pub(crate) async fn prefix_rpc_method_names_with_version(
     Ok(request)
 }
 
+/// Reads at most `max_request_body_size` bytes of the body, like the server does.
+///
+/// Errors are mapped to the server's responses by [try_map_errors_to_responses].
+pub(super) async fn read_limited_body(
+    headers: &http::HeaderMap,
+    body: Body,
+    max_request_body_size: u32,
+) -> Result<(Vec<u8>, bool), BoxError> {
+    read_body(headers, body, max_request_body_size)
+        .await
+        .map_err(|error| match error {
+            GenericTransportError::TooLarge => {
+                BoxError::from(VersioningError::TooLarge(max_request_body_size))
+            }
+            GenericTransportError::Malformed => BoxError::from(VersioningError::Malformed),
+            GenericTransportError::Inner(_) => BoxError::from(VersioningError::Internal),
+        })
+}
+
 pub(crate) fn try_map_errors_to_responses(
     result: Result<Response<Body>, BoxError>,
 ) -> Result<Response<Body>, BoxError> {
@@ -235,7 +235,7 @@ pub async fn get_proof(
         let storage_proofs = input
             .keys
             .iter()
-            .map(|k| contract_state_tree.get_proof(k.view_bits()).map(ProofNodes))
+            .map(|k| contract_state_tree.get_proof(k).map(ProofNodes))
             .collect::<anyhow::Result<Vec<_>>>()
             .context("Get proof from contract state treee")?;
 
@@ -1,6 +1,6 @@
 //! Utilities used for serializing/deserializing sequencer REST API related data.
 
-use ethers::types::{H160, H256};
+use ethers::types::{H160, H256, U256};
 use num_bigint::BigUint;
 use pathfinder_common::{
     BlockNumber, CallParam, ConstructorParam, EthereumAddress, EventData, EventKey, GasPrice,
@@ -239,6 +239,14 @@ serde_with::serde_conv!(
     |s: &str| bytes_from_hex_str::<{ H256::len_bytes() }>(s).map(|b| TransactionVersion(H256::from(b)))
 );
 
+// Like `TransactionVersionAsHexStr` but rejects versions which are not valid field elements.
+serde_with::serde_conv!(
+    pub TransactionVersionAsFeltHexStr,
+    TransactionVersion,
+    |serialize_me: &TransactionVersion| bytes_to_hex_str(serialize_me.0.as_bytes()),
+    |s: &str| Felt::from_hex_str(s).map(|felt| TransactionVersion(H256::from(felt.to_be_bytes())))
+);
+
 serde_with::serde_conv!(
     pub H256AsHexStr,
     ethers::types::H256,
@@ -246,6 +254,56 @@ serde_with::serde_conv!(
     |s: &str| bytes_from_hex_str::<32>(s).map(H256::from)
 );
 
+/// Serializes a [U256] as a JSON integer, as used by the sequencer for fee amounts.
+///
+/// Values are limited to 128 bits, which is plenty for any fee.
+pub struct U256AsBigDecimal;
+
+impl SerializeAs<U256> for U256AsBigDecimal {
+    fn serialize_as<S>(source: &U256, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if source.bits() > 128 {
+            return Err(serde::ser::Error::custom("value exceeds 128 bits"));
+        }
+        serializer.serialize_u128(source.as_u128())
+    }
+}
+
+impl<'de> DeserializeAs<'de, U256> for U256AsBigDecimal {
+    fn deserialize_as<D>(deserializer: D) -> Result<U256, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct U256Visitor;
+
+        impl<'de> Visitor<'de> for U256Visitor {
+            type Value = U256;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a non-negative integer of up to 128 bits")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(U256::from(v))
+            }
+
+            fn visit_u128<E>(self, v: u128) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(U256::from(v))
+            }
+        }
+
+        deserializer.deserialize_u128(U256Visitor)
+    }
+}
+
 pub struct U64AsHexStr(pub u64);
 
 impl serde::Serialize for U64AsHexStr {
@@ -449,6 +507,49 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn transaction_version_as_felt_hex_str() {
+        #[serde_with::serde_as]
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Version(#[serde_as(as = "TransactionVersionAsFeltHexStr")] TransactionVersion);
+
+        // The prefix is optional and odd lengths are fine.
+        for json in [r#""0x1""#, r#""1""#, r#""0x01""#] {
+            let version = serde_json::from_str::<Version>(json).unwrap();
+            assert_eq!(version, Version(TransactionVersion::ONE));
+        }
+
+        // The field modulus is the smallest invalid value, which the relaxed helper accepts.
+        let modulus = r#""0x800000000000011000000000000000000000000000000000000000000000001""#;
+        let error = serde_json::from_str::<Version>(modulus).unwrap_err();
+        assert!(
+            error.to_string().contains("maximum value was exceeded"),
+            "{error}"
+        );
+
+        #[serde_with::serde_as]
+        #[derive(serde::Deserialize)]
+        struct Relaxed(#[serde_as(as = "TransactionVersionAsHexStr")] TransactionVersion);
+        let relaxed = serde_json::from_str::<Relaxed>(modulus).unwrap();
+        assert_eq!(relaxed.0 .0.as_bytes()[0], 0x08);
+    }
+
+    #[test]
+    fn u256_as_big_decimal() {
+        #[serde_with::serde_as]
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Fee(#[serde_as(as = "U256AsBigDecimal")] U256);
+
+        let json = "340282366920938463463374607431768211455";
+        let fee = serde_json::from_str::<Fee>(json).unwrap();
+        assert_eq!(fee, Fee(U256::from(u128::MAX)));
+        assert_eq!(serde_json::to_string(&fee).unwrap(), json);
+
+        serde_json::from_str::<Fee>("340282366920938463463374607431768211456").unwrap_err();
+        serde_json::from_str::<Fee>("-1").unwrap_err();
+        serde_json::from_str::<Fee>(r#""0x1""#).unwrap_err();
+    }
+
     #[test]
     fn zero() {
         const ZERO_HEX_STR: &str = "0x0";
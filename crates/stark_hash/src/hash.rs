@@ -2,7 +2,7 @@ use stark_curve::{AffinePoint, FieldElement, ProjectivePoint, PEDERSEN_P0};
 
 use bitvec::{field::BitField, slice::BitSlice};
 
-use crate::Felt;
+use crate::{Felt, HashChain};
 
 include!(concat!(env!("OUT_DIR"), "/curve_consts.rs"));
 
@@ -41,6 +41,16 @@ pub fn stark_hash(a: Felt, b: Felt) -> Felt {
     Felt::from(result.x)
 }
 
+/// Computes the [Starknet Pedersen hash] on all of `values`, i.e. the hash of a [HashChain]
+/// over them. This is `compute_hash_on_elements` in Starknet's Python code.
+///
+/// [Starknet Pedersen hash]: https://docs.starkware.co/starkex-v3/crypto/pedersen-hash-function
+pub fn stark_hash_many(values: &[Felt]) -> Felt {
+    let mut chain = HashChain::default();
+    values.iter().for_each(|value| chain.update(*value));
+    chain.finalize()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +78,22 @@ mod tests {
         assert_eq!(hash, hash2);
         assert_eq!(hash, expected);
     }
+
+    #[test]
+    fn hash_many() {
+        let values = [1, 2, 3, 4].map(Felt::from_u64);
+
+        // produced by the cairo-lang Python implementation:
+        // `hex(compute_hash_on_elements([1, 2, 3, 4]))`
+        let expected =
+            Felt::from_hex_str("0x66bd4335902683054d08a0572747ea78ebd9e531536fb43125424ca9f902084")
+                .unwrap();
+        assert_eq!(stark_hash_many(&values), expected);
+
+        // `hex(compute_hash_on_elements([]))`
+        let expected =
+            Felt::from_hex_str("0x49ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804")
+                .unwrap();
+        assert_eq!(stark_hash_many(&[]), expected);
+    }
 }
@@ -7,4 +7,4 @@ mod serde;
 
 pub use chain::HashChain;
 pub use felt::{Felt, HexParseError, OverflowError};
-pub use hash::stark_hash;
+pub use hash::{stark_hash, stark_hash_many};
@@ -19,10 +19,10 @@ pub use contract::{CasmClassTable, ClassCommitmentLeavesTable, ContractCodeTable
 pub use ethereum::{EthereumBlocksTable, EthereumTransactionsTable};
 use rusqlite::functions::FunctionFlags;
 pub use state::{
-    CanonicalBlocksTable, ContractsStateTable, EventFilterError, L1StateTable, L1TableBlockId,
-    RefsTable, StarknetBlock, StarknetBlocksBlockId, StarknetBlocksNumberOrLatest,
-    StarknetBlocksTable, StarknetEmittedEvent, StarknetEventFilter, StarknetEventsTable,
-    StarknetTransactionsTable, V02KeyFilter, V03KeyFilter,
+    CanonicalBlocksTable, ContractRootHistoryTable, ContractsStateTable, EventFilterError,
+    L1StateTable, L1TableBlockId, RefsTable, StarknetBlock, StarknetBlocksBlockId,
+    StarknetBlocksNumberOrLatest, StarknetBlocksTable, StarknetEmittedEvent, StarknetEventFilter,
+    StarknetEventsTable, StarknetTransactionsTable, V02KeyFilter, V03KeyFilter,
 };
 pub use state_update::insert_canonical_state_diff;
 
@@ -93,6 +93,35 @@ impl Storage {
         Ok(storage)
     }
 
+    /// Opens an existing, fully migrated database as a read-only [Storage].
+    ///
+    /// All connections have [query_only](https://sqlite.org/pragma.html#pragma_query_only)
+    /// enabled, so any attempt to write fails. Combined with [JournalMode::WAL], this lets
+    /// a pool of readers (e.g. RPC queries) run without blocking the single sync writer.
+    pub fn open_read_only(database_path: PathBuf) -> anyhow::Result<Self> {
+        let manager = SqliteConnectionManager::file(&database_path).with_init(|connection| {
+            setup_connection(connection)?;
+            connection.pragma_update(None, "query_only", true)
+        });
+        let pool = Pool::builder().build(manager)?;
+
+        let revision = schema_version(&*pool.get()?)?;
+        let latest_revision = schema::migrations().len();
+        anyhow::ensure!(
+            revision == latest_revision,
+            "Database must be migrated before opening it read-only ({} != {})",
+            revision,
+            latest_revision
+        );
+
+        let inner = Inner {
+            database_path: Arc::new(database_path),
+            pool,
+        };
+
+        Ok(Storage(inner))
+    }
+
     /// Returns a new Sqlite [Connection] to the database.
     pub fn connection(&self) -> anyhow::Result<PooledConnection> {
         let conn = self.0.pool.get()?;
@@ -298,6 +327,17 @@ mod tests {
         migrate_database(&mut conn).unwrap_err();
     }
 
+    #[test]
+    fn read_only() {
+        let storage = Storage::in_memory().unwrap();
+        let read_only = Storage::open_read_only(storage.path().to_owned()).unwrap();
+
+        let conn = read_only.connection().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), schema::migrations().len());
+        conn.execute("CREATE TABLE test (id INTEGER)", [])
+            .unwrap_err();
+    }
+
     #[test]
     fn foreign_keys_are_enforced() {
         let conn = rusqlite::Connection::open_in_memory().unwrap();
@@ -31,6 +31,7 @@ mod revision_0030;
 mod revision_0031;
 mod revision_0032;
 mod revision_0033;
+mod revision_0034;
 
 type MigrationFn = fn(&rusqlite::Transaction<'_>) -> anyhow::Result<()>;
 
@@ -71,5 +72,6 @@ pub fn migrations() -> &'static [MigrationFn] {
         revision_0031::migrate,
         revision_0032::migrate,
         revision_0033::migrate,
+        revision_0034::migrate,
     ]
 }
@@ -0,0 +1,26 @@
+use anyhow::Context;
+use rusqlite::Transaction;
+
+/// This migration adds the contract_root_history table which tracks the storage root of each
+/// contract after every canonical block which updated its storage.
+///
+/// The table is populated for blocks synced after this migration only.
+pub(crate) fn migrate(tx: &Transaction<'_>) -> anyhow::Result<()> {
+    tx.execute(
+        r"CREATE TABLE contract_root_history (
+    block_number INTEGER REFERENCES canonical_blocks(number) ON DELETE CASCADE,
+    contract_address BLOB NOT NULL,
+    root BLOB NOT NULL
+)",
+        [],
+    )
+    .context("Creating contract_root_history table")?;
+
+    tx.execute(
+        "CREATE INDEX contract_root_history_address_block ON contract_root_history(contract_address, block_number)",
+        [],
+    )
+    .context("Creating index on contract_root_history(contract_address, block_number)")?;
+
+    Ok(())
+}
@@ -1470,6 +1470,44 @@ impl ContractsStateTable {
     }
 }
 
+/// Stores the storage root of a contract after each canonical block which updated its storage.
+pub struct ContractRootHistoryTable {}
+
+impl ContractRootHistoryTable {
+    pub fn insert(
+        tx: &Transaction<'_>,
+        block_number: BlockNumber,
+        contract_address: ContractAddress,
+        root: ContractRoot,
+    ) -> anyhow::Result<()> {
+        tx.execute(
+            "INSERT INTO contract_root_history (block_number, contract_address, root) VALUES (?, ?, ?)",
+            params![block_number, contract_address, root],
+        )?;
+        Ok(())
+    }
+
+    /// Returns all of the contract's historical storage roots, ordered from oldest to newest.
+    pub fn get_roots(
+        tx: &Transaction<'_>,
+        contract_address: ContractAddress,
+    ) -> anyhow::Result<Vec<ContractRoot>> {
+        let mut stmt = tx
+            .prepare_cached(
+                "SELECT root FROM contract_root_history WHERE contract_address = ? ORDER BY block_number",
+            )
+            .context("Preparing statement")?;
+
+        let roots = stmt
+            .query_map([contract_address], |row| row.get(0))
+            .context("Querying contract roots")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Reading contract roots")?;
+
+        Ok(roots)
+    }
+}
+
 /// Stores the canonical Starknet block chain.
 pub struct CanonicalBlocksTable {}
 
@@ -1533,6 +1571,56 @@ mod tests {
         }
     }
 
+    mod contract_root_history {
+        use super::*;
+        use pathfinder_common::felt;
+
+        #[test]
+        fn get_roots() {
+            let storage = Storage::in_memory().unwrap();
+            let mut connection = storage.connection().unwrap();
+            let transaction = connection.transaction().unwrap();
+
+            let contract = ContractAddress::new_or_panic(felt!("0x1"));
+            let other = ContractAddress::new_or_panic(felt!("0x2"));
+            let roots = [ContractRoot(felt!("0xa")), ContractRoot(felt!("0xb"))];
+
+            let blocks = crate::test_utils::create_blocks();
+            for (block, root) in blocks.iter().zip(roots) {
+                let block = &block.block;
+                StarknetBlocksTable::insert(
+                    &transaction,
+                    block,
+                    &StarknetVersion::default(),
+                    StorageCommitment::ZERO,
+                    ClassCommitment::ZERO,
+                )
+                .unwrap();
+                CanonicalBlocksTable::insert(&transaction, block.number, block.hash).unwrap();
+
+                ContractRootHistoryTable::insert(&transaction, block.number, contract, root)
+                    .unwrap();
+            }
+            ContractRootHistoryTable::insert(
+                &transaction,
+                BlockNumber::new_or_panic(1),
+                other,
+                ContractRoot(felt!("0xc")),
+            )
+            .unwrap();
+
+            let result = ContractRootHistoryTable::get_roots(&transaction, contract).unwrap();
+            assert_eq!(result, roots);
+
+            // Reorging the canonical chain removes the history.
+            CanonicalBlocksTable::reorg(&transaction, BlockNumber::new_or_panic(1)).unwrap();
+            let result = ContractRootHistoryTable::get_roots(&transaction, contract).unwrap();
+            assert_eq!(result, roots[..1]);
+            let result = ContractRootHistoryTable::get_roots(&transaction, other).unwrap();
+            assert!(result.is_empty());
+        }
+    }
+
     mod refs {
         use super::*;
 